@@ -1,12 +1,14 @@
 extern crate incremental_computations;
 use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId};
+use nannou::rand::{rngs::StdRng, SeedableRng};
 use incremental_computations::{graph::Graph, graph_coloring::{GraphColoring, ScoreCalcTypeGraphColoring}, graph_coloring_comp::GraphColoringFlags};
 
 use std::rc::Rc;
 
 fn run_graph_coloring(n: i32, score_type: ScoreCalcTypeGraphColoring, flags: Option<GraphColoringFlags>) {
+    let mut rng = StdRng::seed_from_u64(n as u64);
     let mut graph = Graph::new();
-    graph.fill_with_random_points(n);
+    graph.fill_with_random_points_rng(n, &mut rng);
     graph.fill_with_edges_full();
     let rc_graph = Rc::new(graph);
 
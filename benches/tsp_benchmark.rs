@@ -1,16 +1,21 @@
 extern crate incremental_computations;
-use incremental_computations::{graph, tsp::{ScoreCalcTypeTSP, Tsp}};
+use incremental_computations::{graph, tsp::{PivotingRule, ScoreCalcTypeTSP, Tsp}};
 use criterion::{criterion_group, criterion_main, Criterion, BenchmarkId};
+use nannou::rand::{rngs::StdRng, SeedableRng};
 
 use std::rc::Rc;
 
-fn run_tsp(n: i32, score_calc_type: ScoreCalcTypeTSP) {
+fn run_tsp(n: i32, score_calc_type: ScoreCalcTypeTSP, pivoting_rule: PivotingRule) {
+    // every mode is benchmarked on the same instance and starting path
+    let mut rng = StdRng::seed_from_u64(n as u64);
     let mut tsp_graph = graph::Graph::new();
-    tsp_graph.fill_with_random_points(n);
+    tsp_graph.fill_with_random_points_rng(n, &mut rng);
     tsp_graph.fill_with_edges_full();
     let rc_tsp_graph = Rc::new(tsp_graph);
 
     let mut tsp = Tsp::new(Rc::clone(&rc_tsp_graph), score_calc_type);
+    tsp.set_seed(n as u64);
+    tsp.set_pivoting_rule(pivoting_rule);
     tsp.generate_starting_path();
     tsp.tsp().unwrap();
 }
@@ -21,27 +26,44 @@ fn tsp_benchmark(c: &mut Criterion) {
     for n in [10, 20, 50, 100].iter() {
         group.bench_with_input(BenchmarkId::new("Fast", n), n, |b, &n| {
             b.iter(|| {
-                run_tsp(n, ScoreCalcTypeTSP::Fast);
+                run_tsp(n, ScoreCalcTypeTSP::Fast, PivotingRule::FirstImprovement);
             });
         });
 
         group.bench_with_input(BenchmarkId::new("Slow", n), n, |b, &n| {
             b.iter(|| {
-                run_tsp(n, ScoreCalcTypeTSP::Slow);
+                run_tsp(n, ScoreCalcTypeTSP::Slow, PivotingRule::FirstImprovement);
             });
         });
 
         group.bench_with_input(BenchmarkId::new("Incremental", n), n, |b, &n| {
             b.iter(|| {
-                run_tsp(n, ScoreCalcTypeTSP::Incremental);
+                run_tsp(n, ScoreCalcTypeTSP::Incremental, PivotingRule::FirstImprovement);
             });
         });
     }
 
 }
 
+// the pivoting rule decides how many moves are evaluated and taken back per applied move
+fn tsp_pivoting_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tsp_pivoting_benchmark");
+    group.sample_size(10);
+    let n = 50;
+    for (name, pivoting_rule) in [
+        ("First", PivotingRule::FirstImprovement),
+        ("Best", PivotingRule::BestImprovement),
+        ("RandomOrderFirst", PivotingRule::RandomOrderFirst),
+    ] {
+        group.bench_function(BenchmarkId::new("Fast", name), |b| b.iter(|| run_tsp(n, ScoreCalcTypeTSP::Fast, pivoting_rule)));
+        group.bench_function(BenchmarkId::new("Slow", name), |b| b.iter(|| run_tsp(n, ScoreCalcTypeTSP::Slow, pivoting_rule)));
+        group.bench_function(BenchmarkId::new("Incremental", name), |b| b.iter(|| run_tsp(n, ScoreCalcTypeTSP::Incremental, pivoting_rule)));
+    }
+}
+
 criterion_group!(
     benches, 
-    tsp_benchmark
+    tsp_benchmark,
+    tsp_pivoting_benchmark
 );
 criterion_main!(benches);
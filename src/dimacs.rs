@@ -0,0 +1,223 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::graph::Graph;
+
+// Reader and writer for DIMACS graph coloring instances (.col)
+// https://mat.tepper.cmu.edu/COLOR/instances.html
+//
+// c <comment>
+// p edge <nodes> <edges>
+// e <u> <v>
+
+#[derive(Debug)]
+pub enum DimacsError {
+    Io(io::Error),
+    MissingProblemLine,
+    DuplicateProblemLine { line: usize },
+    UnsupportedFormat { line: usize, format: String },
+    InvalidLine { line: usize, content: String },
+    InvalidNumber { line: usize, value: String },
+    InvalidNodeId { line: usize, id: i64 },
+    SelfLoop { line: usize, id: i64 },
+    EdgeCountMismatch { expected: i64, found: i64 },
+}
+
+impl fmt::Display for DimacsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DimacsError::Io(err) => write!(f, "io error: {}", err),
+            DimacsError::MissingProblemLine => write!(f, "problem line 'p edge <nodes> <edges>' is missing"),
+            DimacsError::DuplicateProblemLine { line } => write!(f, "line {}: problem line is defined twice", line),
+            DimacsError::UnsupportedFormat { line, format } => write!(f, "line {}: unsupported format '{}'", line, format),
+            DimacsError::InvalidLine { line, content } => write!(f, "line {}: unexpected '{}'", line, content),
+            DimacsError::InvalidNumber { line, value } => write!(f, "line {}: '{}' is not a number", line, value),
+            DimacsError::InvalidNodeId { line, id } => write!(f, "line {}: node id {} is out of range", line, id),
+            DimacsError::SelfLoop { line, id } => write!(f, "line {}: self loop on node {}", line, id),
+            DimacsError::EdgeCountMismatch { expected, found } => write!(f, "problem line declares {} edges, found {} edge lines", expected, found),
+        }
+    }
+}
+
+impl std::error::Error for DimacsError {}
+
+impl From<io::Error> for DimacsError {
+    fn from(err: io::Error) -> Self {
+        DimacsError::Io(err)
+    }
+}
+
+fn parse_number(line: usize, value: &str) -> Result<i64, DimacsError> {
+    value.parse::<i64>().map_err(|_| DimacsError::InvalidNumber { line, value: value.to_string() })
+}
+
+// Nodes are placed on a circle so that the instance can be drawn,
+// edges get a unit weight. Repeated edges (some instances list both directions) are skipped,
+// but still count towards the number of edges declared on the problem line.
+pub fn parse(input: &str) -> Result<Graph, DimacsError> {
+    let mut graph: Option<Graph> = None;
+    let mut expected_edges = 0;
+    let mut edge_lines = 0;
+
+    for (i, raw) in input.lines().enumerate() {
+        let line = i + 1;
+        let tokens = raw.split_whitespace().collect::<Vec<&str>>();
+
+        match tokens.first() {
+            None | Some(&"c") => continue,
+            Some(&"p") => {
+                if graph.is_some() {
+                    return Err(DimacsError::DuplicateProblemLine { line });
+                }
+                if tokens.len() != 4 {
+                    return Err(DimacsError::InvalidLine { line, content: raw.to_string() });
+                }
+                if tokens[1] != "edge" && tokens[1] != "col" {
+                    return Err(DimacsError::UnsupportedFormat { line, format: tokens[1].to_string() });
+                }
+
+                // node ids are i32, a larger count would wrap
+                let n = i32::try_from(parse_number(line, tokens[2])?)
+                    .ok()
+                    .filter(|&n| n >= 0)
+                    .ok_or_else(|| DimacsError::InvalidNumber { line, value: tokens[2].to_string() })?;
+                expected_edges = parse_number(line, tokens[3])?;
+                if expected_edges < 0 {
+                    return Err(DimacsError::InvalidNumber { line, value: tokens[3].to_string() });
+                }
+
+                let mut g = Graph::new();
+                g.fill_with_circular_layout(n);
+                graph = Some(g);
+            },
+            Some(&"e") => {
+                let g = graph.as_mut().ok_or(DimacsError::MissingProblemLine)?;
+                if tokens.len() != 3 {
+                    return Err(DimacsError::InvalidLine { line, content: raw.to_string() });
+                }
+
+                let n = g.get_number_of_nodes() as i64;
+                let u = parse_number(line, tokens[1])?;
+                let v = parse_number(line, tokens[2])?;
+
+                for id in [u, v] {
+                    if id < 1 || id > n {
+                        return Err(DimacsError::InvalidNodeId { line, id });
+                    }
+                }
+                if u == v {
+                    return Err(DimacsError::SelfLoop { line, id: u });
+                }
+
+                edge_lines += 1;
+                // in range of i32 after the check against n
+                let to_id = |id: i64| i32::try_from(id - 1).map_err(|_| DimacsError::InvalidNodeId { line, id });
+                let (u, v) = (to_id(u)?, to_id(v)?);
+                if g.get_edge_from_lookup(u, v).is_none() {
+                    g.add_edge(u, v, 1);
+                }
+            },
+            // node descriptors ("n <id> <value>") and other optional lines carry nothing we use
+            Some(&"n") | Some(&"x") | Some(&"d") | Some(&"v") => continue,
+            Some(_) => return Err(DimacsError::InvalidLine { line, content: raw.to_string() }),
+        }
+    }
+
+    let graph = graph.ok_or(DimacsError::MissingProblemLine)?;
+    if edge_lines != expected_edges {
+        return Err(DimacsError::EdgeCountMismatch { expected: expected_edges, found: edge_lines });
+    }
+
+    Ok(graph)
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Graph, DimacsError> {
+    let content = fs::read_to_string(path)?;
+    parse(&content)
+}
+
+pub fn write<W: io::Write>(graph: &Graph, out: &mut W) -> io::Result<()> {
+    writeln!(out, "p edge {} {}", graph.get_number_of_nodes(), graph.edges.len())?;
+    for edge in &graph.edges {
+        writeln!(out, "e {} {}", edge.p1 + 1, edge.p2 + 1)?;
+    }
+
+    Ok(())
+}
+
+pub fn to_string(graph: &Graph) -> String {
+    let mut out = Vec::new();
+    write(graph, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+pub fn save<P: AsRef<Path>>(graph: &Graph, path: P) -> io::Result<()> {
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    write(graph, &mut file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Point;
+
+    #[test]
+    fn test_parse() {
+        let input = "\
+c FILE: myciel3.col (truncated)
+c
+p edge 5 6
+e 1 2
+e 1 4
+e 2 3
+e 2 1
+e 3 5
+e 4 5
+";
+        let graph = parse(input).unwrap();
+        assert_eq!(graph.get_number_of_nodes(), 5);
+        assert_eq!(graph.edges.len(), 5, "repeated edge 2-1 should be skipped");
+        assert!(graph.get_edge_from_lookup(0, 1).is_some());
+        assert!(graph.get_edge_from_lookup(3, 4).is_some());
+        assert!(graph.get_edge_from_lookup(0, 4).is_none());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut graph = Graph::new();
+        graph.add_nodes((0..4).map(|_| Point::random()).collect());
+        graph.add_2d_edge(0, 2);
+        graph.add_2d_edge(0, 3);
+        graph.add_2d_edge(1, 2);
+        graph.add_2d_edge(1, 3);
+
+        let output = to_string(&graph);
+        assert_eq!(output, "p edge 4 4\ne 1 3\ne 1 4\ne 2 3\ne 2 4\n");
+
+        let parsed = parse(&output).unwrap();
+        assert_eq!(parsed.get_number_of_nodes(), 4);
+        for edge in &graph.edges {
+            assert!(parsed.get_edge_from_lookup(edge.p1, edge.p2).is_some());
+        }
+        assert_eq!(to_string(&parsed), output);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(parse("c empty\n"), Err(DimacsError::MissingProblemLine)));
+        assert!(matches!(parse("e 1 2\n"), Err(DimacsError::MissingProblemLine)));
+        assert!(matches!(parse("p edge 2 1\np edge 2 1\n"), Err(DimacsError::DuplicateProblemLine { line: 2 })));
+        assert!(matches!(parse("p cnf 2 1\n"), Err(DimacsError::UnsupportedFormat { line: 1, .. })));
+        assert!(matches!(parse("p edge 2 1\ne 1 3\n"), Err(DimacsError::InvalidNodeId { line: 2, id: 3 })));
+        assert!(matches!(parse("p edge 2 1\ne 2 2\n"), Err(DimacsError::SelfLoop { line: 2, id: 2 })));
+        assert!(matches!(parse("p edge 2 1\ne 1 a\n"), Err(DimacsError::InvalidNumber { line: 2, .. })));
+        assert!(matches!(parse("p edge 2 1\ne 1\n"), Err(DimacsError::InvalidLine { line: 2, .. })));
+        assert!(matches!(parse("p edge 2 -1\n"), Err(DimacsError::InvalidNumber { line: 1, .. })));
+        assert!(matches!(parse("p edge -2 1\n"), Err(DimacsError::InvalidNumber { line: 1, .. })));
+        assert!(matches!(parse("c\np edge 4294967298 1\n"), Err(DimacsError::InvalidNumber { line: 2, .. })));
+        assert!(matches!(parse("p edge 3 3\ne 1 2\ne 2 3\n"), Err(DimacsError::EdgeCountMismatch { expected: 3, found: 2 })));
+        assert!(matches!(parse("p edge 3 1\ne 1 2\ne 2 3\n"), Err(DimacsError::EdgeCountMismatch { expected: 1, found: 2 })));
+    }
+}
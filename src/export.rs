@@ -0,0 +1,294 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::graph::{EdgeId, Graph, PointId, Weight};
+use crate::graph_coloring::Color;
+use crate::tsp::TspPath;
+
+// Writers for Graphviz DOT (https://graphviz.org/doc/info/lang.html) and
+// GraphML (http://graphml.graphdrawing.org/), optionally with a solution drawn on top:
+// the edges of a tour are highlighted, a colouring fills the nodes and marks conflicting edges red.
+// Node positions are kept, `neato -n` lays out a DOT file at the coordinates of the graph.
+
+#[derive(Debug, Clone, Copy)]
+pub enum Overlay<'a> {
+    None,
+    Tour(&'a TspPath),
+    Coloring(&'a [Color]),
+}
+
+const TOUR_COLOR: &str = "#1f4fe0";
+const CONFLICT_COLOR: &str = "#e01f1f";
+
+// fill colour of a colour class, hues are spread with the golden angle so neighbouring classes differ
+fn fill_color(color: Color) -> String {
+    let hue = (color.0 as f64 * 0.618_033_988_75).rem_euclid(1.0) * 6.0;
+    let (saturation, value) = (0.45, 0.95);
+
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as i32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let channel = |c: f64| ((c + value - chroma) * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", channel(r), channel(g), channel(b))
+}
+
+// the overlay resolved against the graph
+struct Solution<'a> {
+    tour_edges: HashSet<EdgeId>,
+    // consecutive tour nodes without an edge between them
+    missing_edges: Vec<(PointId, PointId)>,
+    coloring: Option<&'a [Color]>,
+}
+
+impl<'a> Solution<'a> {
+    fn new<W: Weight>(graph: &Graph<W>, overlay: Overlay<'a>) -> io::Result<Solution<'a>> {
+        let n = graph.get_number_of_nodes();
+        let mut solution = Solution { tour_edges: HashSet::new(), missing_edges: Vec::new(), coloring: None };
+
+        match overlay {
+            Overlay::None => {},
+            Overlay::Tour(path) => {
+                if path.len() != n {
+                    return Err(invalid_input(format!("the tour visits {} of {} nodes", path.len(), n)));
+                }
+                for i in 0..path.len() {
+                    let (u, v) = (path[i], path[(i + 1) % path.len()]);
+                    match graph.edges_lookup.get(&graph.lookup_key(u, v)) {
+                        Some(&id) => {
+                            solution.tour_edges.insert(id);
+                        },
+                        None => solution.missing_edges.push((u, v)),
+                    }
+                }
+            },
+            Overlay::Coloring(coloring) => {
+                if coloring.len() != n {
+                    return Err(invalid_input(format!("the colouring has {} colours for {} nodes", coloring.len(), n)));
+                }
+                solution.coloring = Some(coloring);
+            },
+        }
+
+        Ok(solution)
+    }
+
+    fn is_conflict(&self, u: PointId, v: PointId) -> bool {
+        self.coloring.is_some_and(|coloring| coloring[u as usize] == coloring[v as usize])
+    }
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+pub fn write_dot<W: Weight, O: io::Write>(graph: &Graph<W>, overlay: Overlay, out: &mut O) -> io::Result<()> {
+    let solution = Solution::new(graph, overlay)?;
+    let (kind, connector) = if graph.is_directed() { ("digraph", "->") } else { ("graph", "--") };
+
+    writeln!(out, "{} G {{", kind)?;
+    writeln!(out, "  node [shape=circle, style=filled, fillcolor=\"#ffffff\"];")?;
+
+    for (u, node) in graph.get_nodes().iter().enumerate() {
+        write!(out, "  {} [pos=\"{},{}\"", u, node.x, node.y)?;
+        if let Some(coloring) = solution.coloring {
+            let color = coloring[u];
+            write!(out, ", fillcolor=\"{}\", xlabel=\"{}\"", fill_color(color), color.0)?;
+        }
+        writeln!(out, "];")?;
+    }
+
+    for (id, edge) in graph.edges.iter().enumerate() {
+        write!(out, "  {} {} {} [label=\"{:?}\"", edge.p1, connector, edge.p2, edge.weight)?;
+        if solution.tour_edges.contains(&(id as EdgeId)) {
+            write!(out, ", color=\"{}\", penwidth=3", TOUR_COLOR)?;
+        }
+        if solution.is_conflict(edge.p1, edge.p2) {
+            write!(out, ", color=\"{}\", penwidth=2", CONFLICT_COLOR)?;
+        }
+        writeln!(out, "];")?;
+    }
+
+    for &(u, v) in &solution.missing_edges {
+        writeln!(out, "  {} {} {} [color=\"{}\", penwidth=3, style=dashed];", u, connector, v, TOUR_COLOR)?;
+    }
+
+    writeln!(out, "}}")
+}
+
+pub fn write_graphml<W: Weight, O: io::Write>(graph: &Graph<W>, overlay: Overlay, out: &mut O) -> io::Result<()> {
+    let solution = Solution::new(graph, overlay)?;
+    let direction = if graph.is_directed() { "directed" } else { "undirected" };
+
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(out, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">")?;
+    for (id, target, kind) in [
+        ("x", "node", "double"),
+        ("y", "node", "double"),
+        ("color", "node", "int"),
+        ("fill", "node", "string"),
+        ("weight", "edge", "double"),
+    ] {
+        writeln!(out, "  <key id=\"{0}\" for=\"{1}\" attr.name=\"{0}\" attr.type=\"{2}\"/>", id, target, kind)?;
+    }
+    for (id, target) in [("tour", "edge"), ("missing", "edge"), ("conflict", "edge")] {
+        writeln!(out, "  <key id=\"{0}\" for=\"{1}\" attr.name=\"{0}\" attr.type=\"boolean\"><default>false</default></key>", id, target)?;
+    }
+    writeln!(out, "  <graph id=\"G\" edgedefault=\"{}\">", direction)?;
+
+    for (u, node) in graph.get_nodes().iter().enumerate() {
+        write!(out, "    <node id=\"n{}\"><data key=\"x\">{}</data><data key=\"y\">{}</data>", u, node.x, node.y)?;
+        if let Some(coloring) = solution.coloring {
+            let color = coloring[u];
+            write!(out, "<data key=\"color\">{}</data><data key=\"fill\">{}</data>", color.0, fill_color(color))?;
+        }
+        writeln!(out, "</node>")?;
+    }
+
+    for (id, edge) in graph.edges.iter().enumerate() {
+        write!(out, "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\"><data key=\"weight\">{}</data>", id, edge.p1, edge.p2, edge.weight.to_f64())?;
+        if solution.tour_edges.contains(&(id as EdgeId)) {
+            write!(out, "<data key=\"tour\">true</data>")?;
+        }
+        if solution.is_conflict(edge.p1, edge.p2) {
+            write!(out, "<data key=\"conflict\">true</data>")?;
+        }
+        writeln!(out, "</edge>")?;
+    }
+
+    for (i, &(u, v)) in solution.missing_edges.iter().enumerate() {
+        writeln!(out, "    <edge id=\"t{}\" source=\"n{}\" target=\"n{}\"><data key=\"tour\">true</data><data key=\"missing\">true</data></edge>", i, u, v)?;
+    }
+
+    writeln!(out, "  </graph>")?;
+    writeln!(out, "</graphml>")
+}
+
+pub fn to_dot<W: Weight>(graph: &Graph<W>, overlay: Overlay) -> String {
+    let mut out = Vec::new();
+    write_dot(graph, overlay, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+pub fn to_graphml<W: Weight>(graph: &Graph<W>, overlay: Overlay) -> String {
+    let mut out = Vec::new();
+    write_graphml(graph, overlay, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+pub fn save_dot<W: Weight, P: AsRef<Path>>(graph: &Graph<W>, overlay: Overlay, path: P) -> io::Result<()> {
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    write_dot(graph, overlay, &mut file)
+}
+
+pub fn save_graphml<W: Weight, P: AsRef<Path>>(graph: &Graph<W>, overlay: Overlay, path: P) -> io::Result<()> {
+    let mut file = io::BufWriter::new(fs::File::create(path)?);
+    write_graphml(graph, overlay, &mut file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Point;
+
+    fn square() -> Graph {
+        // 0 - 1 - 2 - 3 - 0 with the diagonal 0 - 2
+        let mut graph = Graph::new();
+        graph.add_nodes(vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0), Point::new(1.0, 1.0), Point::new(0.0, 1.5)]);
+        for (u, v, w) in [(0, 1, 1), (1, 2, 1), (2, 3, 2), (3, 0, 2), (0, 2, 3)] {
+            graph.add_edge(u, v, w);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_dot() {
+        let graph = square();
+        assert_eq!(to_dot(&graph, Overlay::None), "\
+graph G {
+  node [shape=circle, style=filled, fillcolor=\"#ffffff\"];
+  0 [pos=\"0,0\"];
+  1 [pos=\"1,0\"];
+  2 [pos=\"1,1\"];
+  3 [pos=\"0,1.5\"];
+  0 -- 1 [label=\"1\"];
+  1 -- 2 [label=\"1\"];
+  2 -- 3 [label=\"2\"];
+  3 -- 0 [label=\"2\"];
+  0 -- 2 [label=\"3\"];
+}
+");
+
+        // 1 -> 3 has no edge and is drawn dashed
+        let tour = to_dot(&graph, Overlay::Tour(&vec![0, 2, 1, 3]));
+        assert!(tour.contains("  0 -- 2 [label=\"3\", color=\"#1f4fe0\", penwidth=3];"));
+        assert!(tour.contains("  1 -- 2 [label=\"1\", color=\"#1f4fe0\", penwidth=3];"));
+        assert!(tour.contains("  0 -- 1 [label=\"1\"];"));
+        assert!(tour.contains("  1 -- 3 [color=\"#1f4fe0\", penwidth=3, style=dashed];"));
+        assert_eq!(tour.matches("penwidth=3").count(), 4);
+
+        let coloring = [Color(0), Color(1), Color(0), Color(1)];
+        let colored = to_dot(&graph, Overlay::Coloring(&coloring));
+        assert!(colored.contains(&format!("  1 [pos=\"1,0\", fillcolor=\"{}\", xlabel=\"1\"];", fill_color(Color(1)))));
+        assert!(colored.contains("  0 -- 2 [label=\"3\", color=\"#e01f1f\", penwidth=2];"));
+        assert_eq!(colored.matches("#e01f1f").count(), 1);
+
+        let mut directed = Graph::<f64>::default();
+        directed.set_directed(true);
+        directed.add_nodes(vec![Point::new(0.0, 0.0), Point::new(2.0, 0.0)]);
+        directed.add_edge(1, 0, 0.5);
+        assert!(to_dot(&directed, Overlay::None).starts_with("digraph G {"));
+        assert!(to_dot(&directed, Overlay::Tour(&vec![0, 1])).contains("  1 -> 0 [label=\"0.5\", color=\"#1f4fe0\", penwidth=3];\n  0 -> 1 [color"));
+    }
+
+    #[test]
+    fn test_graphml() {
+        let graph = square();
+        let plain = to_graphml(&graph, Overlay::None);
+        assert!(plain.contains("<graph id=\"G\" edgedefault=\"undirected\">"));
+        assert!(plain.contains("<node id=\"n3\"><data key=\"x\">0</data><data key=\"y\">1.5</data></node>"));
+        assert!(plain.contains("<edge id=\"e4\" source=\"n0\" target=\"n2\"><data key=\"weight\">3</data></edge>"));
+        assert_eq!(plain.matches("<node ").count(), 4);
+        assert_eq!(plain.matches("<edge ").count(), 5);
+
+        let tour = to_graphml(&graph, Overlay::Tour(&vec![0, 1, 2, 3]));
+        assert_eq!(tour.matches("<data key=\"tour\">true</data>").count(), 4);
+        assert!(!tour.contains("<data key=\"missing\">true</data>"));
+
+        let tour = to_graphml(&graph, Overlay::Tour(&vec![0, 2, 1, 3]));
+        assert!(tour.contains("<edge id=\"t0\" source=\"n1\" target=\"n3\"><data key=\"tour\">true</data><data key=\"missing\">true</data></edge>"));
+
+        let coloring = [Color(0), Color(1), Color(0), Color(2)];
+        let colored = to_graphml(&graph, Overlay::Coloring(&coloring));
+        assert!(colored.contains(&format!("<data key=\"color\">2</data><data key=\"fill\">{}</data>", fill_color(Color(2)))));
+        assert_eq!(colored.matches("<data key=\"conflict\">true</data>").count(), 1);
+        assert!(colored.trim_end().ends_with("</graphml>"));
+    }
+
+    #[test]
+    fn test_invalid_overlay() {
+        let graph = square();
+        let mut out = Vec::new();
+        let err = write_dot(&graph, Overlay::Tour(&vec![0, 1, 2]), &mut out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = write_graphml(&graph, Overlay::Coloring(&[Color(0); 5]), &mut out).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_fill_colors_differ() {
+        let fills = (0..8).map(|c| fill_color(Color(c))).collect::<HashSet<String>>();
+        assert_eq!(fills.len(), 8);
+        assert!(fills.iter().all(|fill| fill.len() == 7 && fill.starts_with('#')));
+    }
+}
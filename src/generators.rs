@@ -0,0 +1,306 @@
+use std::collections::HashSet;
+
+use nannou::rand::seq::SliceRandom;
+use nannou::rand::Rng;
+
+use crate::graph::{Graph, Point, PointId};
+use crate::graph_coloring::Color;
+
+// Random graph families used as benchmark instances.
+// Every generator places the nodes inside the default boundary of the graph
+// and weights the edges with `add_2d_edge`, so the instances can be drawn and used for TSP.
+
+fn add_edge_if_missing(graph: &mut Graph, u: PointId, v: PointId) -> bool {
+    if u == v || graph.get_edge_from_lookup(u, v).is_some() {
+        return false;
+    }
+
+    graph.add_2d_edge(u, v);
+    true
+}
+
+// Erdős–Rényi G(n, m): m distinct edges chosen uniformly at random
+pub fn gnm<R: Rng + ?Sized>(n: i32, m: usize, rng: &mut R) -> Graph {
+    let max_edges = (n as usize) * (n as usize).saturating_sub(1) / 2;
+    assert!(m <= max_edges, "G(n, m) can have at most n * (n - 1) / 2 edges");
+
+    let mut graph = Graph::new();
+    graph.fill_with_random_points_rng(n, rng);
+
+    if 2 * m > max_edges {
+        // dense case, sampling without replacement from all pairs
+        let pairs = (0..n)
+            .flat_map(|u| (u + 1..n).map(move |v| (u, v)))
+            .collect::<Vec<(PointId, PointId)>>();
+
+        for &(u, v) in pairs.choose_multiple(rng, m) {
+            graph.add_2d_edge(u, v);
+        }
+    } else {
+        let mut added = 0;
+        while added < m {
+            let u = rng.gen_range(0..n);
+            let v = rng.gen_range(0..n);
+            if add_edge_if_missing(&mut graph, u, v) {
+                added += 1;
+            }
+        }
+    }
+
+    graph
+}
+
+// random geometric graph: nodes closer than `radius` are connected
+pub fn random_geometric<R: Rng + ?Sized>(n: i32, radius: f64, rng: &mut R) -> Graph {
+    let mut graph = Graph::new();
+    graph.fill_with_random_points_rng(n, rng);
+
+    for u in 0..n {
+        for v in u + 1..n {
+            let nodes = graph.get_nodes();
+            let (a, b) = (&nodes[u as usize], &nodes[v as usize]);
+            if (a.x - b.x).powi(2) + (a.y - b.y).powi(2) <= radius * radius {
+                graph.add_2d_edge(u, v);
+            }
+        }
+    }
+
+    graph
+}
+
+// Barabási–Albert preferential attachment, starting from a clique of m + 1 nodes,
+// every following node attaches to m distinct nodes chosen proportionally to their degree
+pub fn barabasi_albert<R: Rng + ?Sized>(n: i32, m: i32, rng: &mut R) -> Graph {
+    assert!(m >= 1, "Each new node must attach to at least 1 node");
+    assert!(n > m, "Graph must have more than m nodes");
+
+    let mut graph = Graph::new();
+    graph.fill_with_random_points_rng(n, rng);
+
+    // every node appears in this list once per incident edge
+    let mut endpoints: Vec<PointId> = Vec::new();
+    for u in 0..=m {
+        for v in u + 1..=m {
+            graph.add_2d_edge(u, v);
+            endpoints.push(u);
+            endpoints.push(v);
+        }
+    }
+
+    for u in m + 1..n {
+        let mut targets: Vec<PointId> = Vec::new();
+        while targets.len() < m as usize {
+            let v = *endpoints.choose(rng).unwrap();
+            if !targets.contains(&v) {
+                targets.push(v);
+            }
+        }
+
+        for v in targets {
+            graph.add_2d_edge(u, v);
+            endpoints.push(u);
+            endpoints.push(v);
+        }
+    }
+
+    graph
+}
+
+// Watts–Strogatz small world: ring lattice where every node is connected to its k nearest
+// neighbours (k / 2 on each side), each edge is then rewired with probability beta
+pub fn watts_strogatz<R: Rng + ?Sized>(n: i32, k: i32, beta: f64, rng: &mut R) -> Graph {
+    assert!(k % 2 == 0, "k must be even");
+    assert!(k < n, "k must be smaller than the number of nodes");
+
+    let key = |u: PointId, v: PointId| (u.min(v), u.max(v));
+
+    let mut edges: Vec<(PointId, PointId)> = Vec::new();
+    for u in 0..n {
+        for j in 1..=k / 2 {
+            edges.push((u, (u + j) % n));
+        }
+    }
+    let mut present = edges.iter().map(|&(u, v)| key(u, v)).collect::<HashSet<(PointId, PointId)>>();
+
+    for edge in edges.iter_mut() {
+        let (u, v) = *edge;
+        if rng.gen::<f64>() >= beta {
+            continue;
+        }
+
+        let candidates = (0..n)
+            .filter(|&w| w != u && !present.contains(&key(u, w)))
+            .collect::<Vec<PointId>>();
+
+        if let Some(&w) = candidates.choose(rng) {
+            present.remove(&key(u, v));
+            present.insert(key(u, w));
+            *edge = (u, w);
+        }
+    }
+
+    let mut graph = Graph::new();
+    graph.fill_with_circular_layout(n);
+    for (u, v) in edges {
+        graph.add_2d_edge(u, v);
+    }
+
+    graph
+}
+
+// Planted partition k-colourable graph: nodes are split into k balanced hidden colour classes
+// and every pair of nodes from different classes is connected with probability p, a plain G(n, p)
+// between the classes. Degrees and edge counts are not matched to a random graph (no quiet planting),
+// so the hidden classes may be easier to find. The returned colouring is proper, so the chromatic
+// number is at most k.
+pub fn planted_coloring<R: Rng + ?Sized>(n: i32, k: i32, p: f64, rng: &mut R) -> (Graph, Vec<Color>) {
+    assert!(k >= 1 && k <= n, "Number of colours must be between 1 and n");
+
+    let mut coloring = (0..n).map(|u| Color(u % k)).collect::<Vec<Color>>();
+    coloring.shuffle(rng);
+
+    let mut graph = Graph::new();
+    graph.fill_with_random_points_rng(n, rng);
+
+    for u in 0..n {
+        for v in u + 1..n {
+            if coloring[u as usize] != coloring[v as usize] && rng.gen::<f64>() < p {
+                graph.add_2d_edge(u, v);
+            }
+        }
+    }
+
+    (graph, coloring)
+}
+
+// standard normal sample (Box–Muller)
+fn gaussian<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen::<f64>();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+// Clustered TSP instance: cities are normally distributed around uniformly placed centres,
+// `spread` is the standard deviation of a cluster. The graph is complete.
+pub fn clustered_cities<R: Rng + ?Sized>(n: i32, clusters: i32, spread: f64, rng: &mut R) -> Graph {
+    assert!(clusters >= 1, "There must be at least 1 cluster");
+
+    let mut graph = Graph::new();
+    let (top, bottom, left, right) = graph.get_boundary();
+
+    let centres = (0..clusters)
+        .map(|_| Point::new(rng.gen_range(left..right), rng.gen_range(top..bottom)))
+        .collect::<Vec<Point>>();
+
+    let cities = (0..n)
+        .map(|_| {
+            let centre = centres.choose(rng).unwrap();
+            let x = (centre.x + spread * gaussian(rng)).clamp(left, right);
+            let y = (centre.y + spread * gaussian(rng)).clamp(top, bottom);
+            Point::new(x, y)
+        })
+        .collect::<Vec<Point>>();
+
+    graph.add_nodes(cities);
+    graph.fill_with_edges_full();
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::rand::rngs::StdRng;
+    use nannou::rand::SeedableRng;
+
+    fn assert_simple(graph: &Graph) {
+        for edge in &graph.edges {
+            assert_ne!(edge.p1, edge.p2, "Self loop");
+            let id = graph.edges_lookup[&(edge.p1.min(edge.p2), edge.p1.max(edge.p2))];
+            assert!(std::ptr::eq(&graph.edges[id as usize], edge), "Duplicate edge");
+        }
+    }
+
+    #[test]
+    fn test_gnm() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for m in [0, 10, 40, 45] {
+            let graph = gnm(10, m, &mut rng);
+            assert_eq!(graph.get_number_of_nodes(), 10);
+            assert_eq!(graph.edges.len(), m);
+            assert_simple(&graph);
+        }
+    }
+
+    #[test]
+    fn test_random_geometric() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let radius = 25.0;
+        let graph = random_geometric(40, radius, &mut rng);
+        let nodes = graph.get_nodes();
+
+        for u in 0..40 {
+            for v in u + 1..40 {
+                let (a, b) = (&nodes[u as usize], &nodes[v as usize]);
+                let close = ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt() <= radius;
+                assert_eq!(graph.get_edge_from_lookup(u, v).is_some(), close);
+            }
+        }
+    }
+
+    #[test]
+    fn test_barabasi_albert() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let (n, m) = (50, 3);
+        let graph = barabasi_albert(n, m, &mut rng);
+
+        assert_eq!(graph.edges.len() as i32, m * (m + 1) / 2 + (n - m - 1) * m);
+        assert_simple(&graph);
+        for u in 0..n {
+            assert!(graph.get_adjacent_nodes(u).len() as i32 >= m);
+        }
+    }
+
+    #[test]
+    fn test_watts_strogatz() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for beta in [0.0, 0.2, 1.0] {
+            let graph = watts_strogatz(30, 4, beta, &mut rng);
+            assert_eq!(graph.edges.len(), 30 * 4 / 2);
+            assert_simple(&graph);
+        }
+
+        let lattice = watts_strogatz(30, 4, 0.0, &mut rng);
+        for u in 0..30 {
+            assert!(lattice.get_edge_from_lookup(u, (u + 1) % 30).is_some());
+            assert!(lattice.get_edge_from_lookup(u, (u + 2) % 30).is_some());
+        }
+    }
+
+    #[test]
+    fn test_planted_coloring() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let (graph, coloring) = planted_coloring(40, 4, 0.5, &mut rng);
+
+        assert_eq!(coloring.len(), 40);
+        for c in 0..4 {
+            assert_eq!(coloring.iter().filter(|&&color| color == c).count(), 10);
+        }
+        for edge in &graph.edges {
+            assert_ne!(coloring[edge.p1 as usize], coloring[edge.p2 as usize]);
+        }
+        assert!(!graph.edges.is_empty());
+    }
+
+    #[test]
+    fn test_clustered_cities() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let graph = clustered_cities(30, 3, 5.0, &mut rng);
+        let (top, bottom, left, right) = graph.get_boundary();
+
+        assert_eq!(graph.edges.len(), 30 * 29 / 2);
+        for node in graph.get_nodes() {
+            assert!(node.x >= left && node.x <= right);
+            assert!(node.y >= top && node.y <= bottom);
+        }
+    }
+}
@@ -1,5 +1,19 @@
 use nannou::rand;
-use std::collections::HashMap;
+use nannou::rand::seq::SliceRandom;
+use nannou::rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Debug};
+use std::iter::Sum;
+use std::ops::{Add, Sub};
+
+pub mod csr;
+pub mod kdtree;
+pub mod metric;
+pub mod stats;
+
+pub use metric::Metric;
 
 pub const EPS: f64 = 1e-9;
 
@@ -10,182 +24,1131 @@ type Bottom = f64;
 type Left = f64;
 type Right = f64;
 
-#[derive(Debug)]
+// Type of the edge weights, the graph and the solvers are generic over it.
+// Integer weights truncate euclidean distances.
+pub trait Weight: Copy + Debug + Default + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Sum + Serialize + DeserializeOwned + 'static {
+    fn from_f64(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+    // bit pattern used to compare and hash weights stored in the DCG
+    fn to_bits(self) -> u64;
+    // largest representable weight, tour lengths have to stay below it
+    fn max_value() -> Self;
+}
+
+impl Weight for i32 {
+    fn from_f64(value: f64) -> Self {
+        value as i32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn to_bits(self) -> u64 {
+        self as u32 as u64
+    }
+
+    fn max_value() -> Self {
+        i32::MAX
+    }
+}
+
+impl Weight for i64 {
+    fn from_f64(value: f64) -> Self {
+        value as i64
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn to_bits(self) -> u64 {
+        self as u64
+    }
+
+    fn max_value() -> Self {
+        i64::MAX
+    }
+}
+
+impl Weight for f64 {
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn to_bits(self) -> u64 {
+        // 0.0 and -0.0 are the same weight
+        (self + 0.0).to_bits()
+    }
+
+    fn max_value() -> Self {
+        f64::MAX
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
 }
 
 impl Point {
-    fn new(x: f64, y: f64) -> Point {
+    pub fn new(x: f64, y: f64) -> Point {
         Point { x, y }
     }
 
-    fn distance(&self, other: &Point) -> f64 {
-        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    pub fn distance(&self, other: &Point, metric: Metric) -> f64 {
+        metric.distance(self, other)
     }
 
     pub fn random() -> Point {
-        Point::new(rand::random_range(0.0, 100.0), rand::random_range(0.0, 100.0))
+        Point::random_rng(&mut rand::thread_rng())
+    }
+
+    pub fn random_rng<R: Rng + ?Sized>(rng: &mut R) -> Point {
+        Point::new(rng.gen_range(0.0..100.0), rng.gen_range(0.0..100.0))
     }
 }
 
-#[derive(Debug)]
-pub struct Edge {
+// in a directed graph the edge goes from p1 to p2
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "W: Weight")]
+pub struct Edge<W: Weight = i32> {
     pub p1: PointId,
     pub p2: PointId,
-    pub weight: i32,
+    pub weight: W,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Neighbor<W: Weight = i32> {
+    pub node: PointId,
+    pub edge: EdgeId,
+    pub weight: W,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphError {
+    NodeOutOfRange(PointId),
+    SelfLoop(PointId),
+    DuplicateEdge(PointId, PointId),
+    MissingEdge(PointId, PointId),
+    DimensionMismatch { expected: usize, found: usize },
+    NotEmpty,
+    NotEnoughNodes { required: usize, found: usize },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GraphError::NodeOutOfRange(u) => write!(f, "Node id out of range: {}", u),
+            GraphError::SelfLoop(u) => write!(f, "Self edge is not allowed: {}", u),
+            GraphError::DuplicateEdge(u, v) => write!(f, "Edge {}-{} already exists", u, v),
+            GraphError::MissingEdge(u, v) => write!(f, "Edge {}-{} does not exist", u, v),
+            GraphError::DimensionMismatch { expected, found } => {
+                write!(f, "Adjacency list dimension mismatch: expected {}, found {}", expected, found)
+            },
+            GraphError::NotEmpty => write!(f, "Graph must be empty"),
+            GraphError::NotEnoughNodes { required, found } => {
+                write!(f, "Graph must have at least {} nodes, found {}", required, found)
+            },
+        }
+    }
 }
 
-#[derive(Debug, Default)]
-pub struct Graph {
+impl std::error::Error for GraphError {}
+
+// serialized as its nodes, edges, boundary and direction,
+// the lookup structures are rebuilt and validated on load
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "W: Weight", into = "GraphData<W>", try_from = "GraphData<W>")]
+pub struct Graph<W: Weight = i32> {
     nodes: Vec<Point>,
-    pub edges: Vec<Edge>,
+    pub edges: Vec<Edge<W>>,
     adj_list: Vec<Vec<EdgeId>>,
+    // area used to generate random points and layouts
     boundary: (Top, Bottom, Left, Right),
+    // smallest box containing all nodes, None while the graph has no nodes
+    bounding_box: Option<(Top, Bottom, Left, Right)>,
     pub edges_lookup: HashMap<(PointId, PointId), EdgeId>,
+    directed: bool,
+    // weights the edges added by add_2d_edge and the fill_with_edges functions
+    metric: Metric,
 }
 
-impl Graph {
-    pub fn new() -> Graph {
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "W: Weight")]
+struct GraphData<W: Weight> {
+    nodes: Vec<Point>,
+    edges: Vec<Edge<W>>,
+    boundary: (Top, Bottom, Left, Right),
+    directed: bool,
+    #[serde(default)]
+    metric: Metric,
+}
+
+impl<W: Weight> From<Graph<W>> for GraphData<W> {
+    fn from(graph: Graph<W>) -> Self {
+        GraphData {
+            nodes: graph.nodes,
+            edges: graph.edges,
+            boundary: graph.boundary,
+            directed: graph.directed,
+            metric: graph.metric,
+        }
+    }
+}
+
+impl<W: Weight> TryFrom<GraphData<W>> for Graph<W> {
+    type Error = GraphError;
+
+    fn try_from(data: GraphData<W>) -> Result<Self, Self::Error> {
+        let mut graph = Graph { boundary: data.boundary, directed: data.directed, metric: data.metric, ..Graph::default() };
+        graph.add_nodes(data.nodes);
+        for edge in data.edges {
+            graph.try_add_edge(edge.p1, edge.p2, edge.weight)?;
+        }
+
+        Ok(graph)
+    }
+}
+
+impl<W: Weight> Default for Graph<W> {
+    fn default() -> Self {
         Graph {
             nodes: Vec::new(),
             edges: Vec::new(),
             adj_list: Vec::new(),
             boundary: (0.0, 100.0, 0.0, 100.0),
+            bounding_box: None,
             edges_lookup: HashMap::new(),
+            directed: false,
+            metric: Metric::Euclidean,
+        }
+    }
+}
+
+impl Graph {
+    // graph with the default integer weights,
+    // use Graph::<W>::default() for other weight types
+    pub fn new() -> Graph {
+        Graph::default()
+    }
+
+    pub fn new_directed() -> Graph {
+        let mut graph = Graph::default();
+        graph.set_directed(true);
+        graph
+    }
+}
+
+impl<W: Weight> Graph<W> {
+    // in directed mode (u, v) and (v, u) are separate edges with their own weights,
+    // the direction can only be changed before any edge is added
+    pub fn set_directed(&mut self, directed: bool) {
+        assert!(self.edges.is_empty(), "Graph must not have edges");
+        self.directed = directed;
+    }
+
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    // only edges added afterwards are weighted with the new metric
+    pub fn set_metric(&mut self, metric: Metric) {
+        self.metric = metric;
+    }
+
+    pub fn get_metric(&self) -> Metric {
+        self.metric
+    }
+
+    // key of the pair in edges_lookup, undirected edges are stored with the smaller id first
+    pub fn lookup_key(&self, u: PointId, v: PointId) -> (PointId, PointId) {
+        match self.directed {
+            true => (u, v),
+            false => (u.min(v), u.max(v)),
+        }
+    }
+
+    fn other_endpoint(&self, id: EdgeId, u: PointId) -> PointId {
+        let edge = &self.edges[id as usize];
+        match edge.p1 == u {
+            true => edge.p2,
+            false => edge.p1,
         }
     }
 
-    pub fn add_edge(&mut self, u: i32, v: i32, weight: i32) {
+    pub fn add_edge(&mut self, u: i32, v: i32, weight: W) {
+        if let Err(err) = self.try_add_edge(u, v, weight) {
+            panic!("{}", err);
+        }
+    }
+
+    pub fn try_add_edge(&mut self, u: i32, v: i32, weight: W) -> Result<EdgeId, GraphError> {
         let n = self.adj_list.len() as i32;
-        assert!(
-            u >= 0 && u < n && v >= 0 && v < n,
-            "Node id out of range",
-        );
-        assert!(u != v, "Self edge is not allowed");
+        for id in [u, v] {
+            if id < 0 || id >= n {
+                return Err(GraphError::NodeOutOfRange(id));
+            }
+        }
+        if u == v {
+            return Err(GraphError::SelfLoop(u));
+        }
+        if self.get_edge_from_lookup(u, v).is_some() {
+            return Err(GraphError::DuplicateEdge(u, v));
+        }
 
         let id = self.edges.len() as i32;
         let edge = Edge { p1: u, p2: v, weight };
         self.edges.push(edge);
         self.adj_list[u as usize].push(id);
         self.adj_list[v as usize].push(id);
-        self.edges_lookup.insert((u.min(v), u.max(v)), id);
+        self.edges_lookup.insert(self.lookup_key(u, v), id);
+        Ok(id)
+    }
+
+    pub fn remove_edge(&mut self, u: i32, v: i32) -> Edge<W> {
+        match self.try_remove_edge(u, v) {
+            Ok(edge) => edge,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    // the last edge takes over the id of the removed one, so edge ids stay dense
+    pub fn try_remove_edge(&mut self, u: i32, v: i32) -> Result<Edge<W>, GraphError> {
+        let key = self.lookup_key(u, v);
+        let id = self.edges_lookup.remove(&key).ok_or(GraphError::MissingEdge(u, v))?;
+
+        for p in [u, v] {
+            self.adj_list[p as usize].retain(|&e| e != id);
+        }
+
+        let last = self.edges.len() as EdgeId - 1;
+        let edge = self.edges.swap_remove(id as usize);
+
+        if id != last {
+            let (p1, p2) = (self.edges[id as usize].p1, self.edges[id as usize].p2);
+            for p in [p1, p2] {
+                for e in self.adj_list[p as usize].iter_mut().filter(|e| **e == last) {
+                    *e = id;
+                }
+            }
+            let moved_key = self.lookup_key(p1, p2);
+            self.edges_lookup.insert(moved_key, id);
+        }
+
+        Ok(edge)
+    }
+
+    // returns the previous weight
+    pub fn set_edge_weight(&mut self, u: i32, v: i32, weight: W) -> W {
+        match self.try_set_edge_weight(u, v, weight) {
+            Ok(previous) => previous,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    pub fn try_set_edge_weight(&mut self, u: i32, v: i32, weight: W) -> Result<W, GraphError> {
+        let id = *self.edges_lookup.get(&self.lookup_key(u, v)).ok_or(GraphError::MissingEdge(u, v))?;
+        let edge = &mut self.edges[id as usize];
+        Ok(std::mem::replace(&mut edge.weight, weight))
     }
 
     pub fn add_2d_edge(&mut self, u: i32, v: i32) {
-        let weight = self.nodes[u as usize].distance(&self.nodes[v as usize]) as i32;
-        self.add_edge(u, v, weight);
+        if let Err(err) = self.try_add_2d_edge(u, v) {
+            panic!("{}", err);
+        }
+    }
+
+    pub fn try_add_2d_edge(&mut self, u: i32, v: i32) -> Result<EdgeId, GraphError> {
+        let n = self.nodes.len() as i32;
+        for id in [u, v] {
+            if id < 0 || id >= n {
+                return Err(GraphError::NodeOutOfRange(id));
+            }
+        }
+
+        let weight = W::from_f64(self.nodes[u as usize].distance(&self.nodes[v as usize], self.metric));
+        self.try_add_edge(u, v, weight)
     }
 
     pub fn add_nodes(&mut self, points: Vec<Point>) {
         for point in points {
-            self.nodes.push(point);
-            self.adj_list.push(Vec::new());
+            self.push_node(point);
         }
     }
 
+    fn push_node(&mut self, point: Point) {
+        let (top, bottom, left, right) = self.bounding_box.unwrap_or((point.y, point.y, point.x, point.x));
+        self.bounding_box = Some((top.min(point.y), bottom.max(point.y), left.min(point.x), right.max(point.x)));
+        self.nodes.push(point);
+        self.adj_list.push(Vec::new());
+    }
+
     pub fn get_adjacent_nodes(&self, u: PointId) -> Vec<PointId> {
+        self.adjacent_nodes(u).collect()
+    }
+
+    // iterates over the neighbours of u without allocating,
+    // in a directed graph only the outgoing edges are visited
+    pub fn neighbors(&self, u: PointId) -> impl Iterator<Item = Neighbor<W>> + '_ {
         self.adj_list[u as usize]
             .iter()
-            .map(|&id| {
-                let edge = &self.edges[id as usize];
-                match edge.p1 == u {
-                    true => edge.p2,
-                    false => edge.p1,
-                }
+            .filter(move |&&id| !self.directed || self.edges[id as usize].p1 == u)
+            .map(move |&id| Neighbor {
+                node: self.other_endpoint(id, u),
+                edge: id,
+                weight: self.edges[id as usize].weight,
             })
-            .collect()
+    }
+
+    pub fn adjacent_nodes(&self, u: PointId) -> impl Iterator<Item = PointId> + '_ {
+        self.neighbors(u).map(|neighbor| neighbor.node)
+    }
+
+    // out-degree in a directed graph
+    pub fn get_degree(&self, u: PointId) -> usize {
+        match self.directed {
+            true => self.neighbors(u).count(),
+            false => self.adj_list[u as usize].len(),
+        }
     }
 
 
-    pub fn get_edge_from_lookup(&self, u: PointId, v: PointId) -> Option<&Edge> {
-        let key = (u.min(v), u.max(v));
+    pub fn get_edge_from_lookup(&self, u: PointId, v: PointId) -> Option<&Edge<W>> {
+        let key = self.lookup_key(u, v);
         self.edges_lookup.get(&key).map(|&id| &self.edges[id as usize])
     }
 
     pub fn fill_with_random_points(&mut self, n: i32) {
-        assert!(self.nodes.is_empty(), "Graph must be empty");
+        self.fill_with_random_points_rng(n, &mut rand::thread_rng());
+    }
+
+    pub fn fill_with_random_points_rng<R: Rng + ?Sized>(&mut self, n: i32, rng: &mut R) {
+        if let Err(err) = self.try_fill_with_random_points_rng(n, rng) {
+            panic!("{}", err);
+        }
+    }
+
+    pub fn try_fill_with_random_points_rng<R: Rng + ?Sized>(&mut self, n: i32, rng: &mut R) -> Result<(), GraphError> {
+        if !self.nodes.is_empty() {
+            return Err(GraphError::NotEmpty);
+        }
 
         for _ in 0..n {
-            let x = rand::random_range(self.boundary.2, self.boundary.3);
-            let y = rand::random_range(self.boundary.0, self.boundary.1);
-            self.nodes.push(Point::new(x, y));
-            self.adj_list.push(Vec::new());
+            let x = rng.gen_range(self.boundary.2..self.boundary.3);
+            let y = rng.gen_range(self.boundary.0..self.boundary.1);
+            self.push_node(Point::new(x, y));
+        }
+
+        Ok(())
+    }
+
+    // places n points evenly on a circle inside the boundary,
+    // used for instances that come without coordinates
+    pub fn fill_with_circular_layout(&mut self, n: i32) {
+        assert!(self.nodes.is_empty(), "Graph must be empty");
+
+        let (top, bottom, left, right) = self.boundary;
+        let cx = (left + right) / 2.0;
+        let cy = (top + bottom) / 2.0;
+        let r = 0.45 * (right - left).abs().min((bottom - top).abs());
+
+        for i in 0..n {
+            let angle = 2.0 * std::f64::consts::PI * i as f64 / n as f64;
+            self.push_node(Point::new(cx + r * angle.cos(), cy + r * angle.sin()));
+        }
+    }
+
+    // edges can only be generated on a graph with nodes and without edges
+    fn ensure_can_fill_edges(&self) -> Result<(), GraphError> {
+        if !self.edges.is_empty() {
+            return Err(GraphError::NotEmpty);
+        }
+        if self.nodes.len() < 2 {
+            return Err(GraphError::NotEnoughNodes { required: 2, found: self.nodes.len() });
         }
+
+        Ok(())
     }
 
     pub fn fill_with_edges_full(&mut self) {
-        assert!(self.edges.is_empty(), "Graph must have no edges");
-        assert!(self.nodes.len() > 1, "Graph must have at least 2 nodes");
+        if let Err(err) = self.try_fill_with_edges_full() {
+            panic!("{}", err);
+        }
+    }
 
-        let n = self.nodes.len();
+    pub fn try_fill_with_edges_full(&mut self) -> Result<(), GraphError> {
+        self.ensure_can_fill_edges()?;
+
+        // a directed graph gets both directions of every pair
+        let (n, directed) = (self.nodes.len(), self.directed);
         for u in 0..n {
-            for v in u+1..n {
-                self.add_2d_edge(u as i32, v as i32);
+            for v in (0..n).filter(|&v| u < v || (directed && u != v)) {
+                self.try_add_2d_edge(u as i32, v as i32)?;
             }
         }
+
+        Ok(())
     }
 
     pub fn fill_with_edges_stochastic(&mut self, p: f64) {
-        assert!(self.edges.is_empty(), "Graph must have no edges");
-        assert!(self.nodes.len() > 1, "Graph must have at least 2 nodes");
+        self.fill_with_edges_stochastic_rng(p, &mut rand::thread_rng());
+    }
+
+    pub fn fill_with_edges_stochastic_rng<R: Rng + ?Sized>(&mut self, p: f64, rng: &mut R) {
+        if let Err(err) = self.try_fill_with_edges_stochastic_rng(p, rng) {
+            panic!("{}", err);
+        }
+    }
+
+    pub fn try_fill_with_edges_stochastic_rng<R: Rng + ?Sized>(&mut self, p: f64, rng: &mut R) -> Result<(), GraphError> {
+        self.ensure_can_fill_edges()?;
 
         let n = self.nodes.len();
-        // TODO: Check if the graph is connected
+        // the result may be disconnected, see fill_with_edges_stochastic_connected
+        for u in 0..n {
+            for v in u+1..n {
+                if rng.gen::<f64>() < p {
+                    self.try_add_2d_edge(u as i32, v as i32)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn fill_with_edges_stochastic_connected(&mut self, p: f64) {
+        self.fill_with_edges_stochastic_connected_rng(p, &mut rand::thread_rng());
+    }
+
+    pub fn fill_with_edges_stochastic_connected_rng<R: Rng + ?Sized>(&mut self, p: f64, rng: &mut R) {
+        if let Err(err) = self.try_fill_with_edges_stochastic_connected_rng(p, rng) {
+            panic!("{}", err);
+        }
+    }
+
+    // adds a random spanning tree first, so the graph is always connected,
+    // the remaining pairs are connected with probability p
+    pub fn try_fill_with_edges_stochastic_connected_rng<R: Rng + ?Sized>(&mut self, p: f64, rng: &mut R) -> Result<(), GraphError> {
+        self.ensure_can_fill_edges()?;
+
+        let n = self.nodes.len() as i32;
+        let mut order = (0..n).collect::<Vec<PointId>>();
+        order.shuffle(rng);
+
+        for i in 1..order.len() {
+            let parent = order[rng.gen_range(0..i)];
+            self.try_add_2d_edge(order[i], parent)?;
+        }
+
         for u in 0..n {
             for v in u+1..n {
-                if rand::random::<f64>() < p {
-                    self.add_2d_edge(u as i32, v as i32);
+                if self.get_edge_from_lookup(u, v).is_none() && rng.gen::<f64>() < p {
+                    self.try_add_2d_edge(u, v)?;
                 }
             }
         }
+
+        Ok(())
+    }
+
+    // component id for every node, components are numbered from 0 in the order of their smallest node.
+    // Directed graphs are treated as undirected here (weakly connected components), the same holds for bridges
+    pub fn connected_components(&self) -> Vec<usize> {
+        let n = self.nodes.len();
+        let mut component = vec![usize::MAX; n];
+        let mut number_of_components = 0;
+        let mut queue = VecDeque::new();
+
+        for start in 0..n {
+            if component[start] != usize::MAX {
+                continue;
+            }
+
+            component[start] = number_of_components;
+            queue.push_back(start as PointId);
+            while let Some(u) = queue.pop_front() {
+                for v in self.adj_list[u as usize].iter().map(|&id| self.other_endpoint(id, u)) {
+                    if component[v as usize] == usize::MAX {
+                        component[v as usize] = number_of_components;
+                        queue.push_back(v);
+                    }
+                }
+            }
+
+            number_of_components += 1;
+        }
+
+        component
+    }
+
+    pub fn get_number_of_components(&self) -> usize {
+        self.connected_components().iter().max().map_or(0, |&c| c + 1)
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.get_number_of_components() <= 1
+    }
+
+    // edges whose removal disconnects their component (Tarjan's low-link),
+    // iterative so that long paths do not overflow the stack
+    pub fn bridges(&self) -> Vec<EdgeId> {
+        let n = self.nodes.len();
+        let mut discovery = vec![usize::MAX; n];
+        let mut low = vec![0; n];
+        let mut time = 0;
+        let mut bridges = Vec::new();
+
+        for root in 0..n {
+            if discovery[root] != usize::MAX {
+                continue;
+            }
+
+            discovery[root] = time;
+            low[root] = time;
+            time += 1;
+
+            // (node, edge used to enter the node, index of the next edge to visit)
+            let mut stack: Vec<(usize, Option<EdgeId>, usize)> = vec![(root, None, 0)];
+            while let Some(&mut (u, parent_edge, ref mut next)) = stack.last_mut() {
+                if let Some(&id) = self.adj_list[u].get(*next) {
+                    *next += 1;
+                    if Some(id) == parent_edge {
+                        continue;
+                    }
+
+                    let v = self.other_endpoint(id, u as PointId) as usize;
+                    if discovery[v] == usize::MAX {
+                        discovery[v] = time;
+                        low[v] = time;
+                        time += 1;
+                        stack.push((v, Some(id), 0));
+                    } else {
+                        low[u] = low[u].min(discovery[v]);
+                    }
+                } else {
+                    stack.pop();
+                    if let Some(&(parent, _, _)) = stack.last() {
+                        low[parent] = low[parent].min(low[u]);
+                        if low[u] > discovery[parent] {
+                            bridges.push(parent_edge.unwrap());
+                        }
+                    }
+                }
+            }
+        }
+
+        bridges.sort();
+        bridges
     }
 
     pub fn get_number_of_nodes(&self) -> usize {
         self.nodes.len()
     }
 
-    pub fn get_raw_adjacency_list(&self) -> Vec<Vec<i32>> {
-        self.adj_list.iter().enumerate().map(|(i, edges)| {
-            let mut res = edges.iter().map(|&id| self.edges[id as usize].weight).collect::<Vec<i32>>();
-            res.insert(i, 0);
-            res
-        }).collect()
+    // missing edges are reported as 0, which is only meaningful for complete graphs,
+    // use get_distance_matrix to choose the value of a missing edge
+    pub fn get_raw_adjacency_list(&self) -> Vec<Vec<W>> {
+        self.get_distance_matrix(W::default())
+    }
+
+    // dense n x n matrix indexed by node ids, matrix[u][v] is the weight of the edge from u to v,
+    // `no_edge` for pairs that are not connected and 0 on the diagonal
+    pub fn get_distance_matrix(&self, no_edge: W) -> Vec<Vec<W>> {
+        let n = self.nodes.len();
+        let mut matrix = vec![vec![no_edge; n]; n];
+
+        for (u, row) in matrix.iter_mut().enumerate() {
+            row[u] = W::default();
+            for neighbor in self.neighbors(u as PointId) {
+                row[neighbor.node as usize] = neighbor.weight;
+            }
+        }
+
+        matrix
+    }
+
+    // larger than the length of any tour using only existing edges, n edges of at most the largest
+    // weight, so a tour pays it for every missing edge it uses. Capped at get_max_missing_edge_penalty
+    // so that tour lengths cannot overflow, weights above cap / n no longer make it dominate.
+    pub fn get_missing_edge_penalty(&self) -> W {
+        let largest = self.edges.iter().map(|edge| edge.weight.to_f64()).fold(0.0, f64::max);
+        let penalty = self.nodes.len() as f64 * largest + 1.0;
+        W::from_f64(penalty.min(self.get_max_missing_edge_penalty().to_f64()))
+    }
+
+    // largest penalty for which a tour of n missing edges still fits in W,
+    // the margin covers the rounding of i64::MAX to f64
+    pub fn get_max_missing_edge_penalty(&self) -> W {
+        let max = W::max_value().to_f64() * (1.0 - 4.0 * f64::EPSILON);
+        W::from_f64((max / self.nodes.len().max(1) as f64).floor())
+    }
+
+    // every pair of nodes is connected, in both directions for directed graphs
+    pub fn is_complete(&self) -> bool {
+        let n = self.nodes.len();
+        let pairs = if self.directed { n * n.saturating_sub(1) } else { n * n.saturating_sub(1) / 2 };
+        self.edges.len() == pairs
+    }
+
+    // distance matrix in which missing edges cost the missing edge penalty,
+    // complete graphs have no missing edges and skip computing it
+    pub fn get_penalized_distance_matrix(&self) -> Vec<Vec<W>> {
+        let penalty = if self.is_complete() { W::default() } else { self.get_missing_edge_penalty() };
+        self.get_distance_matrix(penalty)
     }
 
     pub fn get_nodes(&self) -> &Vec<Point> {
         &self.nodes
     }
 
+    // boundary used for generation, the nodes themselves may lie anywhere, see get_bounding_box
     pub fn get_boundary(&self) -> (Top, Bottom, Left, Right) {
         self.boundary
     }
+
+    pub fn set_boundary(&mut self, boundary: (Top, Bottom, Left, Right)) {
+        let (top, bottom, left, right) = boundary;
+        assert!(top < bottom && left < right, "Boundary must not be empty");
+        self.boundary = boundary;
+    }
+
+    // falls back to the generation boundary while the graph has no nodes
+    pub fn get_bounding_box(&self) -> (Top, Bottom, Left, Right) {
+        self.bounding_box.unwrap_or(self.boundary)
+    }
 }
 
-// Creating graph from adjacency list and number of nodes
-// only full graph is supported for now
-impl From<(usize, Vec<Vec<i32>>)> for Graph {
-    fn from(data: (usize, Vec<Vec<i32>>)) -> Self {
-        let (size, adj_list) = data;
-        assert!(size > 0, "Graph must have at least 1 node");
-        assert!(size == adj_list.len(), "Adjacency list must have the same size as the number of nodes");
+impl<W: Weight> Graph<W> {
+    // Creating graph from adjacency list and number of nodes,
+    // the points are only used for drawing and are drawn from the given rng
+    // only full graph is supported for now.
+    // An asymmetric matrix creates a directed graph, so that no entry is lost
+    pub fn from_adjacency_matrix_rng<R: Rng + ?Sized>(size: usize, adj_list: Vec<Vec<W>>, rng: &mut R) -> Graph<W> {
+        match Graph::try_from_adjacency_matrix_rng(size, adj_list, rng) {
+            Ok(graph) => graph,
+            Err(err) => panic!("{}", err),
+        }
+    }
 
-        let mut graph = Graph::new();
-        let nodes = (0..size)
-            .map(|_| Point::new(rand::random_range(0.0, 100.0), rand::random_range(0.0, 100.0)))
-            .collect();
+    pub fn try_from_adjacency_matrix(size: usize, adj_list: Vec<Vec<W>>) -> Result<Graph<W>, GraphError> {
+        Graph::try_from_adjacency_matrix_rng(size, adj_list, &mut rand::thread_rng())
+    }
+
+    pub fn try_from_adjacency_matrix_rng<R: Rng + ?Sized>(size: usize, adj_list: Vec<Vec<W>>, rng: &mut R) -> Result<Graph<W>, GraphError> {
+        if size == 0 {
+            return Err(GraphError::NotEnoughNodes { required: 1, found: 0 });
+        }
+        if adj_list.len() != size {
+            return Err(GraphError::DimensionMismatch { expected: size, found: adj_list.len() });
+        }
+        if let Some(row) = adj_list.iter().find(|row| row.len() != size) {
+            return Err(GraphError::DimensionMismatch { expected: size, found: row.len() });
+        }
 
-        graph.add_nodes(nodes);
+        let directed = (0..size).any(|u| (0..u).any(|v| adj_list[u][v] != adj_list[v][u]));
+
+        let mut graph = Graph::default();
+        graph.set_directed(directed);
+        graph.try_fill_with_random_points_rng(size as i32, rng)?;
 
         for (u, adj) in adj_list.iter().enumerate() {
             for (v, &weight) in adj.iter().enumerate() {
-                if u < v {
-                    graph.add_edge(u as i32, v as i32, weight);
+                if u < v || (directed && u != v) {
+                    graph.try_add_edge(u as i32, v as i32, weight)?;
                 }
             }
         }
 
+        Ok(graph)
+    }
+}
+
+impl<W: Weight> From<(usize, Vec<Vec<W>>)> for Graph<W> {
+    fn from(data: (usize, Vec<Vec<W>>)) -> Self {
+        let (size, adj_list) = data;
+        Graph::from_adjacency_matrix_rng(size, adj_list, &mut rand::thread_rng())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::rand::rngs::StdRng;
+    use nannou::rand::SeedableRng;
+
+    fn random_graph(seed: u64) -> Graph {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut graph = Graph::new();
+        graph.fill_with_random_points_rng(30, &mut rng);
+        graph.fill_with_edges_stochastic_rng(0.3, &mut rng);
+        graph
+    }
+
+    #[test]
+    fn test_seeded_generation_is_reproducible() {
+        let a = random_graph(7);
+        let b = random_graph(7);
+
+        assert_eq!(a.get_number_of_nodes(), b.get_number_of_nodes());
+        for (p, q) in a.get_nodes().iter().zip(b.get_nodes()) {
+            assert_eq!((p.x, p.y), (q.x, q.y));
+        }
+
+        let edges_a = a.edges.iter().map(|e| (e.p1, e.p2, e.weight)).collect::<Vec<_>>();
+        let edges_b = b.edges.iter().map(|e| (e.p1, e.p2, e.weight)).collect::<Vec<_>>();
+        assert_eq!(edges_a, edges_b);
+
+        let c = random_graph(8);
+        assert!(a.get_nodes().iter().zip(c.get_nodes()).any(|(p, q)| p.x != q.x));
+    }
+
+    fn graph_with_edges(n: usize, edges: &[(PointId, PointId)]) -> Graph {
+        let mut graph = Graph::new();
+        graph.add_nodes((0..n).map(|_| Point::random()).collect());
+        for &(u, v) in edges {
+            graph.add_edge(u, v, 1);
+        }
         graph
     }
+
+    #[test]
+    fn test_try_add_edge() {
+        let mut graph = graph_with_edges(3, &[(0, 1)]);
+
+        assert_eq!(graph.try_add_edge(0, 3, 1), Err(GraphError::NodeOutOfRange(3)));
+        assert_eq!(graph.try_add_edge(-1, 0, 1), Err(GraphError::NodeOutOfRange(-1)));
+        assert_eq!(graph.try_add_edge(2, 2, 1), Err(GraphError::SelfLoop(2)));
+        assert_eq!(graph.try_add_edge(1, 0, 1), Err(GraphError::DuplicateEdge(1, 0)));
+        assert_eq!(graph.try_add_2d_edge(5, 0), Err(GraphError::NodeOutOfRange(5)));
+        assert_eq!(graph.edges.len(), 1, "Failed insertions must not modify the graph");
+
+        assert_eq!(graph.try_add_edge(1, 2, 1), Ok(1));
+        assert_eq!(graph.get_adjacent_nodes(1), vec![0, 2]);
+    }
+
+    #[test]
+    fn test_try_fill() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut graph = Graph::new();
+        assert_eq!(graph.try_fill_with_edges_full(), Err(GraphError::NotEnoughNodes { required: 2, found: 0 }));
+
+        graph.fill_with_random_points_rng(4, &mut rng);
+        assert_eq!(graph.try_fill_with_random_points_rng(4, &mut rng), Err(GraphError::NotEmpty));
+        assert_eq!(graph.try_fill_with_edges_full(), Ok(()));
+        assert_eq!(graph.try_fill_with_edges_full(), Err(GraphError::NotEmpty));
+        assert_eq!(graph.try_fill_with_edges_stochastic_rng(0.5, &mut rng), Err(GraphError::NotEmpty));
+        assert_eq!(graph.try_fill_with_edges_stochastic_connected_rng(0.5, &mut rng), Err(GraphError::NotEmpty));
+    }
+
+    #[test]
+    fn test_try_from_adjacency_matrix() {
+        assert!(matches!(
+            Graph::<i32>::try_from_adjacency_matrix(0, vec![]),
+            Err(GraphError::NotEnoughNodes { required: 1, found: 0 })
+        ));
+        assert!(matches!(
+            Graph::try_from_adjacency_matrix(3, vec![vec![0, 1, 2], vec![1, 0, 3]]),
+            Err(GraphError::DimensionMismatch { expected: 3, found: 2 })
+        ));
+        assert!(matches!(
+            Graph::try_from_adjacency_matrix(2, vec![vec![0, 1], vec![1]]),
+            Err(GraphError::DimensionMismatch { expected: 2, found: 1 })
+        ));
+
+        let graph = Graph::try_from_adjacency_matrix(2, vec![vec![0, 4], vec![4, 0]]).unwrap();
+        assert_eq!(graph.get_edge_from_lookup(1, 0).unwrap().weight, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "Self edge is not allowed")]
+    fn test_add_edge_panics() {
+        let mut graph = graph_with_edges(2, &[]);
+        graph.add_edge(1, 1, 1);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let mut graph = graph_with_edges(4, &[(0, 1), (2, 0)]);
+        graph.add_edge(3, 0, 7);
+
+        let neighbors = graph.neighbors(0).collect::<Vec<Neighbor>>();
+        assert_eq!(neighbors, vec![
+            Neighbor { node: 1, edge: 0, weight: 1 },
+            Neighbor { node: 2, edge: 1, weight: 1 },
+            Neighbor { node: 3, edge: 2, weight: 7 },
+        ]);
+        assert_eq!(graph.adjacent_nodes(3).collect::<Vec<PointId>>(), vec![0]);
+        assert_eq!(graph.get_degree(0), 3);
+        assert_eq!(graph.get_degree(1), 1);
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let graph = graph_with_edges(6, &[(0, 1), (1, 2), (4, 3)]);
+        assert_eq!(graph.connected_components(), vec![0, 0, 0, 1, 1, 2]);
+        assert_eq!(graph.get_number_of_components(), 3);
+        assert!(!graph.is_connected());
+
+        let graph = graph_with_edges(3, &[(0, 1), (1, 2)]);
+        assert!(graph.is_connected());
+        assert!(Graph::new().is_connected());
+    }
+
+    #[test]
+    fn test_bridges() {
+        // two triangles joined by the edge 2-3, with a pendant node 6
+        let graph = graph_with_edges(7, &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 5), (5, 3), (5, 6)]);
+        assert_eq!(graph.bridges(), vec![3, 7]);
+
+        // every edge of a tree is a bridge
+        let graph = graph_with_edges(5, &[(0, 1), (0, 2), (2, 3), (2, 4)]);
+        assert_eq!(graph.bridges(), vec![0, 1, 2, 3]);
+
+        // no bridges on a cycle
+        let graph = graph_with_edges(4, &[(0, 1), (1, 2), (2, 3), (3, 0)]);
+        assert!(graph.bridges().is_empty());
+    }
+
+    #[test]
+    fn test_stochastic_connected() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for p in [0.0, 0.05, 0.5] {
+            let mut graph = Graph::new();
+            graph.fill_with_random_points_rng(40, &mut rng);
+            graph.fill_with_edges_stochastic_connected_rng(p, &mut rng);
+            assert!(graph.is_connected());
+            assert!(graph.edges.len() >= 39);
+        }
+
+        // a spanning tree only
+        let mut graph = Graph::new();
+        graph.fill_with_random_points_rng(40, &mut rng);
+        graph.fill_with_edges_stochastic_connected_rng(0.0, &mut rng);
+        assert_eq!(graph.bridges().len(), 39);
+    }
+
+    #[test]
+    fn test_directed() {
+        let mut graph = Graph::new_directed();
+        graph.add_nodes((0..3).map(|_| Point::random()).collect());
+        graph.add_edge(0, 1, 5);
+        graph.add_edge(1, 0, 7);
+        graph.add_edge(1, 2, 1);
+
+        assert_eq!(graph.get_edge_from_lookup(0, 1).unwrap().weight, 5);
+        assert_eq!(graph.get_edge_from_lookup(1, 0).unwrap().weight, 7);
+        assert!(graph.get_edge_from_lookup(2, 1).is_none());
+        assert_eq!(graph.try_add_edge(0, 1, 3), Err(GraphError::DuplicateEdge(0, 1)));
+
+        assert_eq!(graph.get_adjacent_nodes(1), vec![0, 2]);
+        assert_eq!(graph.get_adjacent_nodes(2), Vec::<PointId>::new());
+        assert_eq!(graph.get_degree(0), 1);
+        assert!(graph.is_connected());
+    }
+
+    #[test]
+    fn test_asymmetric_matrix() {
+        let symmetric = Graph::from((3, vec![vec![0, 1, 2], vec![1, 0, 3], vec![2, 3, 0]]));
+        assert!(!symmetric.is_directed());
+        assert_eq!(symmetric.edges.len(), 3);
+
+        let matrix = vec![vec![0, 1, 2], vec![4, 0, 3], vec![5, 6, 0]];
+        let asymmetric = Graph::from((3, matrix.clone()));
+        assert!(asymmetric.is_directed());
+        assert_eq!(asymmetric.edges.len(), 6);
+        assert_eq!(asymmetric.get_raw_adjacency_list(), matrix);
+        for u in 0..3 {
+            for v in 0..3 {
+                if u != v {
+                    assert_eq!(asymmetric.get_edge_from_lookup(u, v).unwrap().weight, matrix[u as usize][v as usize]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_distance_matrix() {
+        // sparse graph with edges inserted in shuffled order
+        let mut graph = Graph::new();
+        graph.add_nodes((0..5).map(|_| Point::random()).collect());
+        graph.add_edge(3, 1, 4);
+        graph.add_edge(4, 0, 2);
+        graph.add_edge(1, 0, 7);
+        graph.add_edge(2, 4, 1);
+
+        let matrix = graph.get_distance_matrix(-1);
+        assert_eq!(matrix, vec![
+            vec![0, 7, -1, -1, 2],
+            vec![7, 0, -1, 4, -1],
+            vec![-1, -1, 0, -1, 1],
+            vec![-1, 4, -1, 0, -1],
+            vec![2, -1, 1, -1, 0],
+        ]);
+        // 5 nodes times the largest weight 7, plus one
+        assert_eq!(graph.get_missing_edge_penalty(), 36);
+        assert!(!graph.is_complete());
+
+        // complete graph with edges inserted in reverse order
+        let mut graph = Graph::new();
+        graph.fill_with_random_points(4);
+        for u in (0..4).rev() {
+            for v in (0..u).rev() {
+                graph.add_edge(u, v, 10 * u + v);
+            }
+        }
+
+        let matrix = graph.get_raw_adjacency_list();
+        for u in 0..4 {
+            for v in 0..4 {
+                let expected = if u == v { 0 } else { 10 * u.max(v) + u.min(v) };
+                assert_eq!(matrix[u as usize][v as usize], expected);
+            }
+        }
+        assert!(graph.is_complete());
+        assert_eq!(graph.get_penalized_distance_matrix(), matrix);
+
+        // the penalty is capped so that a tour of 4 missing edges still fits in i32
+        graph.remove_edge(0, 1);
+        graph.set_edge_weight(3, 2, i32::MAX / 8);
+        assert_eq!(graph.get_max_missing_edge_penalty(), i32::MAX / 4);
+        assert_eq!(graph.get_missing_edge_penalty(), i32::MAX / 4);
+        assert_eq!(graph.get_penalized_distance_matrix()[0][1], i32::MAX / 4);
+        assert!(graph.get_missing_edge_penalty().checked_mul(4).is_some());
+        let mut wide: Graph<i64> = Graph::default();
+        wide.fill_with_random_points(3);
+        assert!(wide.get_max_missing_edge_penalty().checked_mul(3).is_some());
+
+        let mut directed = Graph::new_directed();
+        directed.fill_with_random_points(3);
+        directed.add_edge(2, 0, 5);
+        directed.add_edge(0, 2, 3);
+        directed.add_edge(1, 2, 1);
+        assert_eq!(directed.get_distance_matrix(100), vec![vec![0, 100, 3], vec![100, 0, 1], vec![5, 100, 0]]);
+        assert!(!directed.is_complete());
+    }
+
+    #[test]
+    fn test_remove_edge() {
+        let mut graph = Graph::new();
+        graph.add_nodes((0..4).map(|_| Point::random()).collect());
+        graph.add_edge(0, 1, 1);
+        graph.add_edge(1, 2, 2);
+        graph.add_edge(2, 3, 3);
+        graph.add_edge(3, 0, 4);
+
+        let removed = graph.remove_edge(2, 1);
+        assert_eq!((removed.p1, removed.p2, removed.weight), (1, 2, 2));
+        assert_eq!(graph.edges.len(), 3);
+        assert!(graph.get_edge_from_lookup(1, 2).is_none());
+        assert_eq!(graph.try_remove_edge(1, 2).unwrap_err(), GraphError::MissingEdge(1, 2));
+
+        // the last edge (3, 0) took over the id of the removed one
+        assert_eq!(graph.edges_lookup[&(0, 3)], 1);
+        assert_eq!(graph.get_edge_from_lookup(0, 3).unwrap().weight, 4);
+        assert_eq!(graph.get_adjacent_nodes(0), vec![1, 3]);
+        assert_eq!(graph.get_adjacent_nodes(1), vec![0]);
+        assert_eq!(graph.get_adjacent_nodes(2), vec![3]);
+        assert_eq!(graph.bridges().len(), 3);
+
+        assert_eq!(graph.set_edge_weight(3, 0, 10), 4);
+        assert_eq!(graph.get_edge_from_lookup(0, 3).unwrap().weight, 10);
+        assert_eq!(graph.try_set_edge_weight(0, 2, 1), Err(GraphError::MissingEdge(0, 2)));
+
+        graph.add_edge(1, 2, 5);
+        assert_eq!(graph.get_distance_matrix(-1), vec![
+            vec![0, 1, -1, 10],
+            vec![1, 0, 5, -1],
+            vec![-1, 5, 0, 3],
+            vec![10, -1, 3, 0],
+        ]);
+
+        let mut directed = Graph::new_directed();
+        directed.fill_with_random_points(2);
+        directed.add_edge(0, 1, 1);
+        directed.add_edge(1, 0, 2);
+        directed.remove_edge(0, 1);
+        assert!(directed.get_edge_from_lookup(0, 1).is_none());
+        assert_eq!(directed.get_edge_from_lookup(1, 0).unwrap().weight, 2);
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let mut graph = Graph::new();
+        assert_eq!(graph.get_bounding_box(), graph.get_boundary());
+
+        graph.add_nodes(vec![Point::new(-1500.0, 20.0), Point::new(3000.0, -7.5)]);
+        assert_eq!(graph.get_bounding_box(), (-7.5, 20.0, -1500.0, 3000.0));
+        graph.add_nodes(vec![Point::new(0.0, 4000.0)]);
+        assert_eq!(graph.get_bounding_box(), (-7.5, 4000.0, -1500.0, 3000.0));
+        assert_eq!(graph.get_boundary(), (0.0, 100.0, 0.0, 100.0));
+
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut graph = Graph::new();
+        graph.set_boundary((-50.0, -10.0, 1000.0, 1200.0));
+        graph.fill_with_random_points_rng(100, &mut rng);
+        for node in graph.get_nodes() {
+            assert!(node.y >= -50.0 && node.y < -10.0);
+            assert!(node.x >= 1000.0 && node.x < 1200.0);
+        }
+
+        let (top, bottom, left, right) = graph.get_bounding_box();
+        let nodes = graph.get_nodes();
+        assert_eq!(top, nodes.iter().map(|p| p.y).fold(f64::INFINITY, f64::min));
+        assert_eq!(bottom, nodes.iter().map(|p| p.y).fold(f64::NEG_INFINITY, f64::max));
+        assert_eq!(left, nodes.iter().map(|p| p.x).fold(f64::INFINITY, f64::min));
+        assert_eq!(right, nodes.iter().map(|p| p.x).fold(f64::NEG_INFINITY, f64::max));
+    }
+
+    #[test]
+    fn test_metric() {
+        let points = || vec![Point::new(0.0, 0.0), Point::new(3.0, 4.5), Point::new(-1.0, 1.0)];
+
+        let mut graph: Graph<f64> = Graph::default();
+        assert_eq!(graph.get_metric(), Metric::Euclidean);
+        graph.set_metric(Metric::Manhattan);
+        graph.add_nodes(points());
+        graph.fill_with_edges_full();
+        assert_eq!(graph.get_edge_from_lookup(0, 1).unwrap().weight, 7.5);
+        assert_eq!(graph.get_edge_from_lookup(1, 2).unwrap().weight, 7.5);
+
+        // the metric only applies to edges added afterwards
+        graph.set_metric(Metric::Chebyshev);
+        graph.remove_edge(0, 1);
+        graph.add_2d_edge(0, 1);
+        assert_eq!(graph.get_edge_from_lookup(0, 1).unwrap().weight, 4.5);
+        assert_eq!(graph.get_edge_from_lookup(0, 2).unwrap().weight, 2.0);
+
+        let mut directed = Graph::new_directed();
+        directed.set_metric(Metric::Ceil2d);
+        directed.add_nodes(points());
+        directed.fill_with_edges_full();
+        assert_eq!(directed.edges.len(), 6);
+        assert_eq!(directed.get_edge_from_lookup(1, 0).unwrap().weight, 6);
+        assert_eq!(directed.get_edge_from_lookup(0, 1).unwrap().weight, 6);
+    }
+
+    #[test]
+    fn test_weight_types() {
+        let points = || vec![Point::new(0.0, 0.0), Point::new(3.0, 4.0), Point::new(1.0, 1.0)];
+
+        let mut exact: Graph<f64> = Graph::default();
+        exact.add_nodes(points());
+        exact.fill_with_edges_full();
+        assert_eq!(exact.get_edge_from_lookup(0, 1).unwrap().weight, 5.0);
+        assert_eq!(exact.get_edge_from_lookup(0, 2).unwrap().weight, 2.0_f64.sqrt());
+
+        let mut truncated = Graph::new();
+        truncated.add_nodes(points());
+        truncated.fill_with_edges_full();
+        assert_eq!(truncated.get_edge_from_lookup(0, 2).unwrap().weight, 1);
+
+        let big: Graph<i64> = Graph::from((2, vec![vec![0, 1 << 40], vec![1 << 40, 0]]));
+        assert_eq!(big.get_raw_adjacency_list()[0][1], 1 << 40);
+    }
 }
@@ -0,0 +1,93 @@
+use crate::graph::{EdgeId, Graph, Neighbor, PointId, Weight};
+
+// Frozen compressed sparse row copy of the graph topology.
+// The neighbours of u are stored contiguously in targets[offsets[u]..offsets[u + 1]],
+// which keeps the hot loops of the score calculation cache friendly.
+#[derive(Debug, Clone, Default)]
+pub struct CsrGraph<W: Weight = i32> {
+    offsets: Vec<usize>,
+    targets: Vec<PointId>,
+    edge_ids: Vec<EdgeId>,
+    weights: Vec<W>,
+}
+
+impl<W: Weight> CsrGraph<W> {
+    pub fn get_number_of_nodes(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    pub fn get_degree(&self, u: PointId) -> usize {
+        self.offsets[u as usize + 1] - self.offsets[u as usize]
+    }
+
+    pub fn adjacent_nodes(&self, u: PointId) -> &[PointId] {
+        &self.targets[self.offsets[u as usize]..self.offsets[u as usize + 1]]
+    }
+
+    pub fn neighbors(&self, u: PointId) -> impl Iterator<Item = Neighbor<W>> + '_ {
+        (self.offsets[u as usize]..self.offsets[u as usize + 1]).map(move |i| Neighbor {
+            node: self.targets[i],
+            edge: self.edge_ids[i],
+            weight: self.weights[i],
+        })
+    }
+}
+
+impl<W: Weight> From<&Graph<W>> for CsrGraph<W> {
+    fn from(graph: &Graph<W>) -> Self {
+        let n = graph.get_number_of_nodes();
+        let mut csr = CsrGraph {
+            offsets: Vec::with_capacity(n + 1),
+            targets: Vec::with_capacity(2 * graph.edges.len()),
+            edge_ids: Vec::with_capacity(2 * graph.edges.len()),
+            weights: Vec::with_capacity(2 * graph.edges.len()),
+        };
+
+        csr.offsets.push(0);
+        for u in 0..n as PointId {
+            for neighbor in graph.neighbors(u) {
+                csr.targets.push(neighbor.node);
+                csr.edge_ids.push(neighbor.edge);
+                csr.weights.push(neighbor.weight);
+            }
+            csr.offsets.push(csr.targets.len());
+        }
+
+        csr
+    }
+}
+
+impl<W: Weight> Graph<W> {
+    pub fn to_csr(&self) -> CsrGraph<W> {
+        CsrGraph::from(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Point;
+
+    #[test]
+    fn test_csr_matches_graph() {
+        let mut graph = Graph::new();
+        graph.add_nodes((0..5).map(|_| Point::random()).collect());
+        graph.add_edge(0, 1, 3);
+        graph.add_edge(4, 0, 1);
+        graph.add_edge(1, 3, 2);
+        graph.add_edge(2, 3, 5);
+
+        let csr = graph.to_csr();
+        assert_eq!(csr.get_number_of_nodes(), 5);
+
+        for u in 0..5 {
+            assert_eq!(csr.get_degree(u), graph.get_degree(u));
+            assert_eq!(csr.adjacent_nodes(u), graph.get_adjacent_nodes(u).as_slice());
+            assert_eq!(csr.neighbors(u).collect::<Vec<Neighbor>>(), graph.neighbors(u).collect::<Vec<Neighbor>>());
+        }
+
+        assert_eq!(csr.adjacent_nodes(0), &[1, 4]);
+        assert_eq!(csr.adjacent_nodes(2), &[3]);
+        assert_eq!(CsrGraph::<i32>::default().get_number_of_nodes(), 0);
+    }
+}
@@ -0,0 +1,264 @@
+use crate::graph::{Graph, Point, PointId, Weight};
+
+// Which nodes are kept as candidate neighbours of a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Candidates {
+    // the k nearest nodes
+    Nearest(usize),
+    // the k nearest nodes in each of the four quadrants around the node,
+    // which avoids candidate lists that only point into one dense cluster
+    Quadrant(usize),
+}
+
+// 2-d tree over the node coordinates. The tree is stored implicitly in `ids`:
+// the median of ids[lo..hi] sits at (lo + hi) / 2, the nodes left of it are not greater
+// in the split coordinate and the nodes right of it are not smaller.
+#[derive(Debug, Clone)]
+pub struct KdTree {
+    points: Vec<(f64, f64)>,
+    ids: Vec<PointId>,
+}
+
+// search state, `best` is kept sorted by (distance, id) and holds at most k nodes
+struct Query {
+    target: (f64, f64),
+    exclude: Option<PointId>,
+    // closed region the results have to lie in, [x, y] lower and upper bounds
+    low: [f64; 2],
+    high: [f64; 2],
+    k: usize,
+    best: Vec<(f64, PointId)>,
+}
+
+fn coordinate(point: (f64, f64), axis: usize) -> f64 {
+    if axis == 0 { point.0 } else { point.1 }
+}
+
+impl Query {
+    fn contains(&self, point: (f64, f64)) -> bool {
+        (0..2).all(|axis| {
+            let value = coordinate(point, axis);
+            self.low[axis] <= value && value <= self.high[axis]
+        })
+    }
+
+    fn worst(&self) -> f64 {
+        if self.best.len() < self.k { f64::INFINITY } else { self.best[self.k - 1].0 }
+    }
+
+    fn offer(&mut self, distance: f64, id: PointId) {
+        if self.best.len() == self.k && (distance, id) >= self.best[self.k - 1] {
+            return;
+        }
+
+        let position = self.best.partition_point(|&entry| entry < (distance, id));
+        self.best.insert(position, (distance, id));
+        self.best.truncate(self.k);
+    }
+}
+
+impl KdTree {
+    pub fn new(nodes: &[Point]) -> KdTree {
+        let mut tree = KdTree {
+            points: nodes.iter().map(|node| (node.x, node.y)).collect(),
+            ids: (0..nodes.len() as PointId).collect(),
+        };
+
+        let n = tree.ids.len();
+        tree.build(0, n, 0);
+        tree
+    }
+
+    fn build(&mut self, lo: usize, hi: usize, axis: usize) {
+        if hi - lo <= 1 {
+            return;
+        }
+
+        let mid = (lo + hi) / 2;
+        let points = &self.points;
+        self.ids[lo..hi].select_nth_unstable_by(mid - lo, |&a, &b| {
+            coordinate(points[a as usize], axis).total_cmp(&coordinate(points[b as usize], axis))
+        });
+
+        self.build(lo, mid, 1 - axis);
+        self.build(mid + 1, hi, 1 - axis);
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    fn search(&self, lo: usize, hi: usize, axis: usize, query: &mut Query) {
+        if lo >= hi {
+            return;
+        }
+
+        let mid = (lo + hi) / 2;
+        let id = self.ids[mid];
+        let point = self.points[id as usize];
+
+        if query.exclude != Some(id) && query.contains(point) {
+            let dx = point.0 - query.target.0;
+            let dy = point.1 - query.target.1;
+            query.offer(dx * dx + dy * dy, id);
+        }
+
+        let split = coordinate(point, axis);
+        let diff = coordinate(query.target, axis) - split;
+        // the left half can only hold points of the region if the region reaches below the split
+        let left_possible = query.low[axis] <= split;
+        let right_possible = query.high[axis] >= split;
+
+        let (near, far) = if diff < 0.0 {
+            ((lo, mid, left_possible), (mid + 1, hi, right_possible))
+        } else {
+            ((mid + 1, hi, right_possible), (lo, mid, left_possible))
+        };
+
+        if near.2 {
+            self.search(near.0, near.1, 1 - axis, query);
+        }
+        if far.2 && diff * diff <= query.worst() {
+            self.search(far.0, far.1, 1 - axis, query);
+        }
+    }
+
+    fn query(&self, target: (f64, f64), exclude: Option<PointId>, low: [f64; 2], high: [f64; 2], k: usize) -> Vec<PointId> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let mut query = Query { target, exclude, low, high, k, best: Vec::with_capacity(k + 1) };
+        self.search(0, self.ids.len(), 0, &mut query);
+        query.best.into_iter().map(|(_, id)| id).collect()
+    }
+
+    // the k nearest nodes to u without u itself, closest first (ties broken by id)
+    pub fn nearest(&self, u: PointId, k: usize) -> Vec<PointId> {
+        let inf = f64::INFINITY;
+        self.query(self.points[u as usize], Some(u), [-inf; 2], [inf; 2], k)
+    }
+
+    // the k nearest nodes to an arbitrary position
+    pub fn nearest_to(&self, point: &Point, k: usize) -> Vec<PointId> {
+        let inf = f64::INFINITY;
+        self.query((point.x, point.y), None, [-inf; 2], [inf; 2], k)
+    }
+
+    // the k nearest nodes in every quadrant around u, nodes on the axes belong to
+    // both adjacent quadrants and are only listed once, closest first
+    pub fn quadrant_neighbors(&self, u: PointId, k: usize) -> Vec<PointId> {
+        let (x, y) = self.points[u as usize];
+        let inf = f64::INFINITY;
+
+        let mut found: Vec<(f64, PointId)> = Vec::new();
+        for (low, high) in [
+            ([x, y], [inf, inf]),
+            ([-inf, y], [x, inf]),
+            ([-inf, -inf], [x, y]),
+            ([x, -inf], [inf, y]),
+        ] {
+            for v in self.query((x, y), Some(u), low, high, k) {
+                let (vx, vy) = self.points[v as usize];
+                found.push(((vx - x).powi(2) + (vy - y).powi(2), v));
+            }
+        }
+
+        found.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        found.dedup_by_key(|entry| entry.1);
+        found.into_iter().map(|(_, v)| v).collect()
+    }
+
+    pub fn candidate_lists(&self, candidates: Candidates) -> Vec<Vec<PointId>> {
+        (0..self.len() as PointId)
+            .map(|u| match candidates {
+                Candidates::Nearest(k) => self.nearest(u, k),
+                Candidates::Quadrant(k) => self.quadrant_neighbors(u, k),
+            })
+            .collect()
+    }
+}
+
+impl<W: Weight> Graph<W> {
+    pub fn to_kd_tree(&self) -> KdTree {
+        KdTree::new(self.get_nodes())
+    }
+
+    // candidate neighbours of every node by euclidean distance of the coordinates,
+    // independent of the edges of the graph
+    pub fn get_candidate_lists(&self, candidates: Candidates) -> Vec<Vec<PointId>> {
+        self.to_kd_tree().candidate_lists(candidates)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nannou::rand::rngs::StdRng;
+    use nannou::rand::SeedableRng;
+
+    fn brute_force(nodes: &[Point], u: usize, k: usize, filter: impl Fn(&Point) -> bool) -> Vec<PointId> {
+        let mut order = (0..nodes.len())
+            .filter(|&v| v != u && filter(&nodes[v]))
+            .map(|v| ((nodes[v].x - nodes[u].x).powi(2) + (nodes[v].y - nodes[u].y).powi(2), v as PointId))
+            .collect::<Vec<(f64, PointId)>>();
+        order.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        order.into_iter().take(k).map(|(_, v)| v).collect()
+    }
+
+    #[test]
+    fn test_nearest_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut graph = Graph::new();
+        graph.fill_with_random_points_rng(300, &mut rng);
+        // duplicated coordinates and points on a line
+        graph.add_nodes(vec![Point::new(50.0, 50.0), Point::new(50.0, 50.0), Point::new(50.0, 20.0), Point::new(50.0, 80.0)]);
+
+        let nodes = graph.get_nodes();
+        let tree = graph.to_kd_tree();
+        assert_eq!(tree.len(), nodes.len());
+
+        for u in 0..nodes.len() {
+            for k in [0, 1, 5, 12] {
+                assert_eq!(tree.nearest(u as PointId, k), brute_force(nodes, u, k, |_| true));
+            }
+        }
+
+        assert_eq!(tree.nearest(0, 1000).len(), nodes.len() - 1);
+        assert_eq!(tree.nearest_to(&Point::new(50.0, 50.1), 2), vec![300, 301]);
+        assert!(KdTree::new(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_quadrant_neighbors() {
+        let mut rng = StdRng::seed_from_u64(12);
+        let mut graph = Graph::new();
+        graph.fill_with_random_points_rng(200, &mut rng);
+        let nodes = graph.get_nodes();
+        let lists = graph.get_candidate_lists(Candidates::Quadrant(2));
+
+        for (u, list) in lists.iter().enumerate() {
+            let (x, y) = (nodes[u].x, nodes[u].y);
+            let mut expected = brute_force(nodes, u, 2, |p| p.x >= x && p.y >= y);
+            expected.extend(brute_force(nodes, u, 2, |p| p.x <= x && p.y >= y));
+            expected.extend(brute_force(nodes, u, 2, |p| p.x <= x && p.y <= y));
+            expected.extend(brute_force(nodes, u, 2, |p| p.x >= x && p.y <= y));
+
+            let mut sorted = list.clone();
+            sorted.sort();
+            expected.sort();
+            expected.dedup();
+            assert_eq!(sorted, expected);
+        }
+
+        // a corner node only has neighbours in one quadrant
+        let mut corner = Graph::new();
+        corner.add_nodes(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0), Point::new(2.0, 1.0), Point::new(3.0, 3.0)]);
+        assert_eq!(corner.to_kd_tree().quadrant_neighbors(0, 2), vec![1, 2]);
+        assert_eq!(corner.get_candidate_lists(Candidates::Nearest(2))[3], vec![2, 1]);
+    }
+}
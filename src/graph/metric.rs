@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+use crate::graph::Point;
+
+// Distance function used to weight edges between two points.
+// The TSPLIB variants round like the reference implementation of TSPLIB95,
+// so their distances are integral and fit integer weights exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Metric {
+    #[default]
+    Euclidean,
+    Manhattan,
+    Chebyshev,
+    // great-circle distance in kilometres, x is the latitude and y the longitude in degrees
+    Haversine,
+    // TSPLIB EUC_2D, euclidean rounded to the nearest integer
+    Euc2d,
+    // TSPLIB CEIL_2D, euclidean rounded up
+    Ceil2d,
+    // TSPLIB ATT, pseudo euclidean
+    Att,
+    // TSPLIB GEO, coordinates are latitude and longitude given as DDD.MM
+    Geo,
+}
+
+const EARTH_RADIUS: f64 = 6371.0;
+
+fn nint(x: f64) -> f64 {
+    (x + 0.5).trunc()
+}
+
+fn euclidean(a: &Point, b: &Point) -> f64 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
+
+// GEO coordinates are given as DDD.MM (degrees and minutes)
+fn geo_radians(x: f64) -> f64 {
+    // TSPLIB uses this truncated value of pi for its reference distances
+    #[allow(clippy::approx_constant)]
+    const PI: f64 = 3.141592;
+    let deg = x.trunc();
+    let min = x - deg;
+    PI * (deg + 5.0 * min / 3.0) / 180.0
+}
+
+impl Metric {
+    pub fn distance(&self, a: &Point, b: &Point) -> f64 {
+        match self {
+            Metric::Euclidean => euclidean(a, b),
+            Metric::Manhattan => (a.x - b.x).abs() + (a.y - b.y).abs(),
+            Metric::Chebyshev => (a.x - b.x).abs().max((a.y - b.y).abs()),
+            Metric::Haversine => {
+                let (lat_a, lat_b) = (a.x.to_radians(), b.x.to_radians());
+                let d_lat = lat_b - lat_a;
+                let d_lon = (b.y - a.y).to_radians();
+                let h = (d_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_lon / 2.0).sin().powi(2);
+                2.0 * EARTH_RADIUS * h.sqrt().min(1.0).asin()
+            },
+            Metric::Euc2d => nint(euclidean(a, b)),
+            Metric::Ceil2d => euclidean(a, b).ceil(),
+            Metric::Att => {
+                let r = (euclidean(a, b).powi(2) / 10.0).sqrt();
+                let t = nint(r);
+                if t < r { t + 1.0 } else { t }
+            },
+            Metric::Geo => {
+                const RRR: f64 = 6378.388;
+                let (lat_a, lon_a) = (geo_radians(a.x), geo_radians(a.y));
+                let (lat_b, lon_b) = (geo_radians(b.x), geo_radians(b.y));
+                let q1 = (lon_a - lon_b).cos();
+                let q2 = (lat_a - lat_b).cos();
+                let q3 = (lat_a + lat_b).cos();
+                (RRR * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos() + 1.0).trunc()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics() {
+        let a = Point::new(0.0, 0.0);
+        let b = Point::new(3.0, -4.5);
+
+        assert!((Metric::Euclidean.distance(&a, &b) - 29.25_f64.sqrt()).abs() < 1e-12);
+        assert_eq!(Metric::Manhattan.distance(&a, &b), 7.5);
+        assert_eq!(Metric::Chebyshev.distance(&a, &b), 4.5);
+        // sqrt(29.25) = 5.41
+        assert_eq!(Metric::Euc2d.distance(&a, &b), 5.0);
+        assert_eq!(Metric::Ceil2d.distance(&a, &b), 6.0);
+        // sqrt(2.925) = 1.71 -> nint 2
+        assert_eq!(Metric::Att.distance(&a, &b), 2.0);
+
+        for metric in [Metric::Euclidean, Metric::Manhattan, Metric::Chebyshev, Metric::Haversine, Metric::Geo] {
+            assert_eq!(metric.distance(&b, &a), metric.distance(&a, &b));
+            assert_eq!(metric.distance(&a, &a), if metric == Metric::Geo { 1.0 } else { 0.0 });
+        }
+    }
+
+    #[test]
+    fn test_haversine() {
+        // Paris to London is about 344 km
+        let paris = Point::new(48.8566, 2.3522);
+        let london = Point::new(51.5074, -0.1278);
+        let d = Metric::Haversine.distance(&paris, &london);
+        assert!((d - 343.5).abs() < 1.0, "{}", d);
+
+        // a quarter of the equator and antipodal points
+        let quarter = Metric::Haversine.distance(&Point::new(0.0, 0.0), &Point::new(0.0, 90.0));
+        assert!((quarter - EARTH_RADIUS * std::f64::consts::FRAC_PI_2).abs() < 1e-6);
+        let half = Metric::Haversine.distance(&Point::new(10.0, 20.0), &Point::new(-10.0, -160.0));
+        assert!((half - EARTH_RADIUS * std::f64::consts::PI).abs() < 1e-3);
+    }
+}
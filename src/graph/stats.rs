@@ -0,0 +1,325 @@
+use std::collections::BTreeSet;
+use std::fmt;
+
+use crate::graph::{Graph, PointId, Weight};
+
+// Features describing an instance, used to tag benchmark results.
+// Directed graphs are treated as undirected for everything related to degrees and cliques.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphStats {
+    pub nodes: usize,
+    pub edges: usize,
+    // edges relative to the edges of the complete graph of the same kind
+    pub density: f64,
+    // degree_distribution[d] is the number of nodes with degree d
+    pub degree_distribution: Vec<usize>,
+    pub min_degree: usize,
+    pub max_degree: usize,
+    pub mean_degree: f64,
+    // upper bound on the chromatic number by Brooks' theorem
+    pub brooks_bound: usize,
+    // size of a clique found greedily, a lower bound on the chromatic number
+    pub clique_lower_bound: usize,
+    // largest minimum degree seen while peeling off nodes, degeneracy + 1 colours always suffice
+    pub degeneracy: usize,
+    pub weights: Option<WeightStats>,
+}
+
+impl fmt::Display for GraphStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={} m={} density={:.3} degree={}..{} clique>={} colours<={}",
+            self.nodes,
+            self.edges,
+            self.density,
+            self.min_degree,
+            self.max_degree,
+            self.clique_lower_bound,
+            self.brooks_bound.min(self.degeneracy + 1),
+        )
+    }
+}
+
+// sorted neighbours of every node ignoring edge directions, without duplicates
+fn undirected_adjacency<W: Weight>(graph: &Graph<W>) -> Vec<Vec<PointId>> {
+    let mut adjacency = vec![Vec::new(); graph.get_number_of_nodes()];
+    for edge in &graph.edges {
+        adjacency[edge.p1 as usize].push(edge.p2);
+        adjacency[edge.p2 as usize].push(edge.p1);
+    }
+
+    for neighbors in adjacency.iter_mut() {
+        neighbors.sort_unstable();
+        neighbors.dedup();
+    }
+
+    adjacency
+}
+
+// Matula–Beck: repeatedly removes a node of minimum remaining degree.
+// Returns the removal order and the degeneracy of the graph.
+fn degeneracy_ordering_of(adjacency: &[Vec<PointId>]) -> (Vec<PointId>, usize) {
+    let n = adjacency.len();
+    let mut degree = adjacency.iter().map(|neighbors| neighbors.len()).collect::<Vec<usize>>();
+    let max_degree = degree.iter().copied().max().unwrap_or(0);
+
+    let mut buckets: Vec<BTreeSet<PointId>> = vec![BTreeSet::new(); max_degree + 1];
+    for (u, &d) in degree.iter().enumerate() {
+        buckets[d].insert(u as PointId);
+    }
+
+    let mut removed = vec![false; n];
+    let mut order = Vec::with_capacity(n);
+    let mut degeneracy = 0;
+    let mut lowest: usize = 0;
+
+    for _ in 0..n {
+        // removing a node lowers the degree of its neighbours by at most one
+        lowest = lowest.saturating_sub(1);
+        while buckets[lowest].is_empty() {
+            lowest += 1;
+        }
+
+        let u = buckets[lowest].pop_first().unwrap();
+        removed[u as usize] = true;
+        degeneracy = degeneracy.max(lowest);
+        order.push(u);
+
+        for &v in &adjacency[u as usize] {
+            if !removed[v as usize] {
+                let d = degree[v as usize];
+                buckets[d].remove(&v);
+                buckets[d - 1].insert(v);
+                degree[v as usize] = d - 1;
+            }
+        }
+    }
+
+    (order, degeneracy)
+}
+
+// grows a clique from every node, adding the neighbours with the highest degree first
+fn greedy_clique_of(adjacency: &[Vec<PointId>]) -> Vec<PointId> {
+    let mut best: Vec<PointId> = Vec::new();
+
+    for (u, neighbors) in adjacency.iter().enumerate() {
+        if neighbors.len() < best.len() {
+            continue;
+        }
+
+        let mut candidates = neighbors.clone();
+        candidates.sort_by_key(|&v| (std::cmp::Reverse(adjacency[v as usize].len()), v));
+
+        let mut clique = vec![u as PointId];
+        for v in candidates {
+            if clique.iter().all(|&w| adjacency[v as usize].binary_search(&w).is_ok()) {
+                clique.push(v);
+            }
+        }
+
+        if clique.len() > best.len() {
+            best = clique;
+        }
+    }
+
+    best.sort_unstable();
+    best
+}
+
+fn is_complete_or_odd_cycle(adjacency: &[Vec<PointId>], component: &[PointId]) -> bool {
+    let size = component.len();
+    let degrees = component.iter().map(|&u| adjacency[u as usize].len());
+
+    let complete = degrees.clone().all(|d| d == size - 1);
+    let odd_cycle = size % 2 == 1 && size >= 3 && degrees.clone().all(|d| d == 2);
+    complete || odd_cycle
+}
+
+impl<W: Weight> Graph<W> {
+    // order in which the nodes are peeled off by minimum degree, colouring the nodes
+    // greedily in the reverse order uses at most degeneracy + 1 colours
+    pub fn degeneracy_ordering(&self) -> (Vec<PointId>, usize) {
+        degeneracy_ordering_of(&undirected_adjacency(self))
+    }
+
+    pub fn greedy_clique(&self) -> Vec<PointId> {
+        greedy_clique_of(&undirected_adjacency(self))
+    }
+
+    // Brooks: a connected graph needs at most max degree colours unless it is a complete graph
+    // or an odd cycle, which need one more. The bound of the graph is the largest of its components.
+    pub fn brooks_bound(&self) -> usize {
+        let adjacency = undirected_adjacency(self);
+        let component_ids = self.connected_components();
+        let number_of_components = component_ids.iter().max().map_or(0, |&c| c + 1);
+
+        let mut components: Vec<Vec<PointId>> = vec![Vec::new(); number_of_components];
+        for (u, &c) in component_ids.iter().enumerate() {
+            components[c].push(u as PointId);
+        }
+
+        components
+            .iter()
+            .map(|component| {
+                let max_degree = component.iter().map(|&u| adjacency[u as usize].len()).max().unwrap_or(0);
+                if is_complete_or_odd_cycle(&adjacency, component) { max_degree + 1 } else { max_degree }
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn weight_stats(&self) -> Option<WeightStats> {
+        if self.edges.is_empty() {
+            return None;
+        }
+
+        let weights = self.edges.iter().map(|edge| edge.weight.to_f64()).collect::<Vec<f64>>();
+        let count = weights.len() as f64;
+        let mean = weights.iter().sum::<f64>() / count;
+        let variance = weights.iter().map(|w| (w - mean).powi(2)).sum::<f64>() / count;
+
+        Some(WeightStats {
+            min: weights.iter().copied().fold(f64::INFINITY, f64::min),
+            max: weights.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            mean,
+            std_dev: variance.sqrt(),
+        })
+    }
+
+    pub fn stats(&self) -> GraphStats {
+        let n = self.get_number_of_nodes();
+        let m = self.edges.len();
+        let adjacency = undirected_adjacency(self);
+        let degrees = adjacency.iter().map(|neighbors| neighbors.len()).collect::<Vec<usize>>();
+        let max_degree = degrees.iter().copied().max().unwrap_or(0);
+
+        let mut degree_distribution = vec![0; max_degree + 1];
+        for &d in &degrees {
+            degree_distribution[d] += 1;
+        }
+
+        let pairs = n * n.saturating_sub(1);
+        let possible_edges = if self.is_directed() { pairs } else { pairs / 2 };
+
+        GraphStats {
+            nodes: n,
+            edges: m,
+            density: if possible_edges == 0 { 0.0 } else { m as f64 / possible_edges as f64 },
+            degree_distribution,
+            min_degree: degrees.iter().copied().min().unwrap_or(0),
+            max_degree,
+            mean_degree: if n == 0 { 0.0 } else { degrees.iter().sum::<usize>() as f64 / n as f64 },
+            brooks_bound: self.brooks_bound(),
+            clique_lower_bound: greedy_clique_of(&adjacency).len(),
+            degeneracy: degeneracy_ordering_of(&adjacency).1,
+            weights: self.weight_stats(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Point;
+    use nannou::rand::rngs::StdRng;
+    use nannou::rand::seq::index;
+    use nannou::rand::SeedableRng;
+
+    fn graph_with_edges(n: usize, edges: &[(PointId, PointId, i32)]) -> Graph {
+        let mut graph = Graph::new();
+        graph.add_nodes((0..n).map(|_| Point::random()).collect());
+        for &(u, v, w) in edges {
+            graph.add_edge(u, v, w);
+        }
+        graph
+    }
+
+    #[test]
+    fn test_stats() {
+        // triangle 0 - 1 - 2 with a tail 2 - 3 - 4 and the isolated node 5
+        let graph = graph_with_edges(6, &[(0, 1, 1), (1, 2, 2), (0, 2, 3), (2, 3, 4), (3, 4, 5)]);
+        let stats = graph.stats();
+
+        assert_eq!(stats.nodes, 6);
+        assert_eq!(stats.edges, 5);
+        assert!((stats.density - 5.0 / 15.0).abs() < 1e-12);
+        assert_eq!(stats.degree_distribution, vec![1, 1, 3, 1]);
+        assert_eq!((stats.min_degree, stats.max_degree), (0, 3));
+        assert!((stats.mean_degree - 10.0 / 6.0).abs() < 1e-12);
+        assert_eq!(stats.brooks_bound, 3);
+        assert_eq!(stats.clique_lower_bound, 3);
+        assert_eq!(stats.degeneracy, 2);
+
+        let weights = stats.weights.clone().unwrap();
+        assert_eq!((weights.min, weights.max, weights.mean), (1.0, 5.0, 3.0));
+        assert!((weights.std_dev - 2.0_f64.sqrt()).abs() < 1e-12);
+
+        assert_eq!(stats.to_string(), "n=6 m=5 density=0.333 degree=0..3 clique>=3 colours<=3");
+        assert_eq!(Graph::new().stats().weights, None);
+    }
+
+    #[test]
+    fn test_brooks_bound() {
+        let odd_cycle = graph_with_edges(5, &[(0, 1, 1), (1, 2, 1), (2, 3, 1), (3, 4, 1), (4, 0, 1)]);
+        assert_eq!(odd_cycle.brooks_bound(), 3);
+        let even_cycle = graph_with_edges(4, &[(0, 1, 1), (1, 2, 1), (2, 3, 1), (3, 0, 1)]);
+        assert_eq!(even_cycle.brooks_bound(), 2);
+
+        let mut complete = Graph::new();
+        complete.fill_with_random_points(5);
+        complete.fill_with_edges_full();
+        assert_eq!(complete.brooks_bound(), 5);
+        assert_eq!(complete.greedy_clique(), vec![0, 1, 2, 3, 4]);
+        assert_eq!(complete.degeneracy_ordering().1, 4);
+
+        // a single edge is a complete graph, the star around it only needs its max degree
+        let components = graph_with_edges(6, &[(0, 1, 1), (2, 3, 1), (2, 4, 1), (2, 5, 1)]);
+        assert_eq!(components.brooks_bound(), 3);
+    }
+
+    #[test]
+    fn test_degeneracy_ordering() {
+        let mut rng = StdRng::seed_from_u64(8);
+        // K4 where every further node is attached to 3 earlier nodes
+        let mut graph = graph_with_edges(60, &[(0, 1, 1), (0, 2, 1), (0, 3, 1), (1, 2, 1), (1, 3, 1), (2, 3, 1)]);
+        for u in 4..60 {
+            for v in index::sample(&mut rng, u, 3) {
+                graph.add_edge(u as PointId, v as PointId, 1);
+            }
+        }
+        let (order, degeneracy) = graph.degeneracy_ordering();
+
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..60).collect::<Vec<PointId>>());
+        // every node has at most `degeneracy` neighbours that are removed after it
+        let mut position = vec![0; 60];
+        for (i, &u) in order.iter().enumerate() {
+            position[u as usize] = i;
+        }
+        for u in 0..60 {
+            let later = graph.adjacent_nodes(u).filter(|&v| position[v as usize] > position[u as usize]).count();
+            assert!(later <= degeneracy);
+        }
+        // the graph is exactly 3-degenerate
+        assert_eq!(degeneracy, 3);
+
+        let clique = graph.greedy_clique();
+        for (i, &u) in clique.iter().enumerate() {
+            for &v in &clique[i + 1..] {
+                assert!(graph.get_edge_from_lookup(u, v).is_some());
+            }
+        }
+        assert!(clique.len() >= 4);
+    }
+}
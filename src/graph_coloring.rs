@@ -1,8 +1,9 @@
 use std::rc::Rc;
+use serde::{Deserialize, Serialize};
 use crate::graph::{Graph, PointId};
 use crate::graph_coloring_comp::{GraphColoringComp, GraphColoringFlags};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Color(pub i32);
 
 impl PartialEq<Color> for i32 {
@@ -19,6 +20,9 @@ impl PartialEq<i32> for Color {
 
 pub struct GraphColoring {
     graph: Rc<Graph>,
+    // copy of the topology used by the naive score calculations, built from the CSR form of the graph
+    // and kept up to date per node by edge edits
+    adjacency: Vec<Vec<PointId>>,
     pub coloring: Vec<Color>,
     pub number_of_colors: i32,
     pub history: Vec<Vec<Color>>,
@@ -59,6 +63,7 @@ impl GraphColoring {
 
 
         GraphColoring {
+            adjacency: Self::adjacency_lists(&graph),
             graph,
             coloring,
             number_of_colors: 1,
@@ -68,6 +73,35 @@ impl GraphColoring {
         }
     }
 
+    fn adjacency_lists(graph: &Graph) -> Vec<Vec<PointId>> {
+        let csr = graph.to_csr();
+        (0..csr.get_number_of_nodes() as PointId).map(|u| csr.adjacent_nodes(u).to_vec()).collect()
+    }
+
+    pub fn add_edge(&mut self, u: PointId, v: PointId) {
+        Rc::make_mut(&mut self.graph).add_2d_edge(u, v);
+        self.adjacency[u as usize].push(v);
+        self.adjacency[v as usize].push(u);
+
+        if let Some(comp) = self.comp.as_mut() {
+            comp.add_edge(u, v);
+        }
+    }
+
+    pub fn remove_edge(&mut self, u: PointId, v: PointId) {
+        Rc::make_mut(&mut self.graph).remove_edge(u, v);
+        for (a, b) in [(u, v), (v, u)] {
+            let neighbours = &mut self.adjacency[a as usize];
+            if let Some(pos) = neighbours.iter().position(|&w| w == b) {
+                neighbours.swap_remove(pos);
+            }
+        }
+
+        if let Some(comp) = self.comp.as_mut() {
+            comp.remove_edge(u, v);
+        }
+    }
+
     fn calculate_score_slow(&self) -> i32 {
         let mut score: i32 = 0;
 
@@ -89,7 +123,7 @@ impl GraphColoring {
             for u in 0..self.graph.get_number_of_nodes() as i32 {
                 if self.coloring[u as usize] == Color(color) {
                     amount += 1;
-                    for v in self.graph.get_adjacent_nodes(u) {
+                    for &v in &self.adjacency[u as usize] {
                         if self.coloring[v as usize] == Color(color) {
                             illegal_edges += 1;
                         }
@@ -113,7 +147,7 @@ impl GraphColoring {
             let color = self.coloring[u as usize].0;
             colors_freq[color as usize] += 1;
 
-            for v in self.graph.get_adjacent_nodes(u) {
+            for &v in &self.adjacency[u as usize] {
                 if self.coloring[v as usize] == color {
                     violating_edges_freq[color as usize] += 1;
                 }
@@ -328,6 +362,36 @@ mod tests {
         assert_eq!(score, -9, "Slow: score is incorrect");
     }
 
+    #[test]
+    fn test_topology_updates() {
+        let graph_rc = Rc::new(create_testing_graph());
+
+        for score_type in [ScoreCalcTypeGraphColoring::Fast, ScoreCalcTypeGraphColoring::Slow, ScoreCalcTypeGraphColoring::Incremental] {
+            let mut graph_coloring = GraphColoring::new(
+                Rc::clone(&graph_rc),
+                score_type,
+                Some(GraphColoringFlags::default())
+            );
+            assert_eq!(graph_coloring.graph_coloring(), -9);
+
+            // 0 and 2 both have colour 1: 2 * (2 * 1 - 2) - 4 - 1
+            graph_coloring.add_edge(0, 2);
+            assert_eq!(graph_coloring.calc_score(), -5);
+            assert_eq!(graph_coloring.calculate_score_slow(), -5);
+
+            graph_coloring.remove_edge(0, 2);
+            graph_coloring.remove_edge(1, 2);
+            assert_eq!(graph_coloring.calc_score(), -9);
+
+            let score = graph_coloring.graph_coloring();
+            assert_eq!(score, graph_coloring.calculate_score_naive());
+            assert!(score <= -9);
+        }
+
+        // the shared graph is left untouched
+        assert_eq!(graph_rc.edges.len(), 7);
+    }
+
     #[test]
     fn test_graph_coloring_incremental() {
         let graph = create_testing_graph();
@@ -3,7 +3,7 @@ use adapton::engine::*;
 use adapton::reflect;
 
 use crate::diagnostics::Diagnostics;
-use crate::graph::Graph;
+use crate::graph::{Graph, PointId};
 
 #[derive(Debug, Default)]
 pub struct GraphColoringFlags {
@@ -47,16 +47,33 @@ impl GraphColoringFlags {
 
 pub struct GraphColoringComp {
     input_nodes_layer: Vec<Art<i32>>,
+    // topology is an input as well, cell u holds the sorted neighbours of u,
+    // so edge changes only invalidate the branches that read the affected nodes.
+    // The lists are shared behind an Rc so that reading a cell does not copy them
+    adjacency_layer: Rc<Vec<Art<Rc<Vec<PointId>>>>>,
     computation_nodes_layer: Vec<Art<i32>>,
     result: Option<Art<i32>>,
     sealed: bool,
     max_number_of_colours: i32,
     used_colours: usize,
     diagnostics: Option<Diagnostics>,
-    graph: Rc<Graph>,
     flags: GraphColoringFlags
 }
 
+// counts the edges between the active nodes, every edge is seen from its smaller endpoint
+fn count_invalid_edges(active: &[bool], adjacency_layer: &[Art<Rc<Vec<PointId>>>]) -> i32 {
+    let mut invalid_edges = 0;
+    for (u, _) in active.iter().enumerate().filter(|(_, &is_active)| is_active) {
+        for &v in get!(adjacency_layer[u]).iter() {
+            if v as usize > u && active[v as usize] {
+                invalid_edges += 1;
+            }
+        }
+    }
+
+    invalid_edges
+}
+
 impl GraphColoringComp {
     pub fn new(graph: Rc<Graph>, n: usize, flags: GraphColoringFlags) -> GraphColoringComp {
         manage::init_dcg();
@@ -68,16 +85,25 @@ impl GraphColoringComp {
         let input_nodes_layer = (0..n).map(|_| {
             cell!(0)
         }).collect();
+
+        let adjacency_layer = (0..n).map(|u| {
+            let mut neighbours = match u < graph.get_number_of_nodes() {
+                true => graph.get_adjacent_nodes(u as PointId),
+                false => Vec::new()
+            };
+            neighbours.sort();
+            cell!(Rc::new(neighbours))
+        }).collect();
         
         GraphColoringComp {
             input_nodes_layer,
+            adjacency_layer: Rc::new(adjacency_layer),
             computation_nodes_layer: Vec::new(),
             result: None,
             sealed: false,
             max_number_of_colours: n as i32,
             used_colours: 1,
             diagnostics: None,
-            graph,
             flags
         }
     }
@@ -107,6 +133,28 @@ impl GraphColoringComp {
         set(&self.input_nodes_layer[idx], val);
     }
 
+    pub fn add_edge(&mut self, u: PointId, v: PointId) {
+        self.ensure_unsealed();
+        for (a, b) in [(u, v), (v, u)] {
+            let mut neighbours = get!(self.adjacency_layer[a as usize]);
+            if let Err(pos) = neighbours.binary_search(&b) {
+                Rc::make_mut(&mut neighbours).insert(pos, b);
+                set(&self.adjacency_layer[a as usize], neighbours);
+            }
+        }
+    }
+
+    pub fn remove_edge(&mut self, u: PointId, v: PointId) {
+        self.ensure_unsealed();
+        for (a, b) in [(u, v), (v, u)] {
+            let mut neighbours = get!(self.adjacency_layer[a as usize]);
+            if let Ok(pos) = neighbours.binary_search(&b) {
+                Rc::make_mut(&mut neighbours).remove(pos);
+                set(&self.adjacency_layer[a as usize], neighbours);
+            }
+        }
+    }
+
     pub fn create_computation_graph(&mut self) {
         let root_node = self.create_root_node();
         self.result = Some(root_node);
@@ -171,33 +219,17 @@ impl GraphColoringComp {
     fn create_computation_layer(&self, guards_layer: &Guards) -> Art<i32> {
         match self.flags.merge_computation_layers {
             true => {
-                let graph_rc = Rc::clone(&self.graph);
+                let adjacency_layer = Rc::clone(&self.adjacency_layer);
 
                 match guards_layer {
                     Guards::Normal(guards_layer) => {
                         let guards_layer_clone = guards_layer.clone();
                         thunk!({
-                            let mut nodes: Vec<usize> = Vec::new();
-                            for (i, g) in guards_layer_clone.iter().enumerate() {
-                                // let is_active = force(&g);
-                                if get!(g) {
-                                    // push if node is in active state for this colour
-                                    nodes.push(i);
-                                }
-                            }
-                            
-                            let mut invalid_edges = 0 as i32;
-                            for i in 0..nodes.len() {
-                                for j in i+1..nodes.len() {
-                                    let edge = graph_rc.get_edge_from_lookup(nodes[i] as i32, nodes[j] as i32);
-                                    match edge {
-                                        Some(_) => invalid_edges += 1,
-                                        None => ()
-                                    }
-                                }
-                            }
+                            // active state of every node for this colour
+                            let active = guards_layer_clone.iter().map(|g| get!(g)).collect::<Vec<bool>>();
+                            let invalid_edges = count_invalid_edges(&active, &adjacency_layer);
         
-                            let vertices_of_colour = nodes.len() as i32;
+                            let vertices_of_colour = active.iter().filter(|&&is_active| is_active).count() as i32;
                             let result = 2 * vertices_of_colour * invalid_edges - vertices_of_colour.pow(2);
                             result as i32
                         })
@@ -205,27 +237,14 @@ impl GraphColoringComp {
                     Guards::Firewall(guards_layer) => {
                         let guards_layer_clone = guards_layer.clone();
                         thunk!({
-                            let mut nodes: Vec<usize> = Vec::new();
-                            for (i, g) in guards_layer_clone.iter().enumerate() {
+                            // active state of every node for this colour
+                            let active = guards_layer_clone.iter().map(|g| {
                                 let is_active = force(&g);
-                                if get!(is_active) {
-                                    // push if node is in active state for this colour
-                                    nodes.push(i);
-                                }
-                            }
-                            
-                            let mut invalid_edges = 0 as i32;
-                            for i in 0..nodes.len() {
-                                for j in i+1..nodes.len() {
-                                    let edge = graph_rc.get_edge_from_lookup(nodes[i] as i32, nodes[j] as i32);
-                                    match edge {
-                                        Some(_) => invalid_edges += 1,
-                                        None => ()
-                                    }
-                                }
-                            }
+                                get!(is_active)
+                            }).collect::<Vec<bool>>();
+                            let invalid_edges = count_invalid_edges(&active, &adjacency_layer);
         
-                            let vertices_of_colour = nodes.len() as i32;
+                            let vertices_of_colour = active.iter().filter(|&&is_active| is_active).count() as i32;
                             let result = 2 * vertices_of_colour * invalid_edges - vertices_of_colour.pow(2);
                             result as i32
                         })
@@ -253,59 +272,26 @@ impl GraphColoringComp {
     }
 
     fn create_invalid_edges_node(&self, guards_layer: &Guards) -> Art<i32> {
-        let graph_rc = Rc::clone(&self.graph);
+        let adjacency_layer = Rc::clone(&self.adjacency_layer);
 
         match guards_layer {
             Guards::Normal(guards_layer) => {
                 let guards_layer_clone = guards_layer.clone();
                 thunk![{
-                    let mut nodes: Vec<usize> = Vec::new();
-                    for (i, g) in guards_layer_clone.iter().enumerate() {
-                        // let is_active = force(&g);
-                        if get!(g) {
-                            // push if node is in active state for this colour
-                            nodes.push(i);
-                        }
-                    }
-                    
-                    let mut invalid_edges = 0;
-                    for i in 0..nodes.len() {
-                        for j in i+1..nodes.len() {
-                            let edge = graph_rc.get_edge_from_lookup(nodes[i] as i32, nodes[j] as i32);
-                            match edge {
-                                Some(_) => invalid_edges += 1,
-                                None => ()
-                            }
-                        }
-                    }
-
-                    invalid_edges
+                    // active state of every node for this colour
+                    let active = guards_layer_clone.iter().map(|g| get!(g)).collect::<Vec<bool>>();
+                    count_invalid_edges(&active, &adjacency_layer)
                 }]
             },
             Guards::Firewall(guards_layer) => {
                 let guards_layer_clone = guards_layer.clone();
                 thunk![{
-                    let mut nodes: Vec<usize> = Vec::new();
-                    for (i, g) in guards_layer_clone.iter().enumerate() {
+                    // active state of every node for this colour
+                    let active = guards_layer_clone.iter().map(|g| {
                         let is_active = force(&g);
-                        if get!(is_active) {
-                            // push if node is in active state for this colour
-                            nodes.push(i);
-                        }
-                    }
-                    
-                    let mut invalid_edges = 0;
-                    for i in 0..nodes.len() {
-                        for j in i+1..nodes.len() {
-                            let edge = graph_rc.get_edge_from_lookup(nodes[i] as i32, nodes[j] as i32);
-                            match edge {
-                                Some(_) => invalid_edges += 1,
-                                None => ()
-                            }
-                        }
-                    }
-
-                    invalid_edges
+                        get!(is_active)
+                    }).collect::<Vec<bool>>();
+                    count_invalid_edges(&active, &adjacency_layer)
                 }]
             }
         }
@@ -468,7 +454,8 @@ mod tests {
         let diagnostics = graph_coloring_comp.diagnostics;
         
         if let Some(diag) = diagnostics {
-            assert!(diag.cells_count == 3, "Cells count should be 3");
+            // 3 input cells and 3 adjacency cells
+            assert!(diag.cells_count == 6, "Cells count should be 6");
             assert!(diag.thunks_count == 3, "Thunks count should be 12");
         }
     }
@@ -479,7 +466,8 @@ mod tests {
 
         graph_coloring_comp.seal();
         if let Some(diag) = graph_coloring_comp.diagnostics {
-            assert_eq!(diag.cells_count, 4, "Cells count should be 4");
+            // 4 input cells and 4 adjacency cells
+            assert_eq!(diag.cells_count, 8, "Cells count should be 8");
 
             // granular_layer: 16
             // guards_layer: 4
@@ -497,7 +485,8 @@ mod tests {
 
         graph_coloring_comp.seal();
         if let Some(diag) = graph_coloring_comp.diagnostics {
-            assert_eq!(diag.cells_count, 4, "Cells count should be 4");
+            // 4 input cells and 4 adjacency cells
+            assert_eq!(diag.cells_count, 8, "Cells count should be 8");
 
             // granular_layer: 16
             // computations_layer: 4
@@ -513,7 +502,8 @@ mod tests {
 
         graph_coloring_comp.seal();
         if let Some(diag) = graph_coloring_comp.diagnostics {
-            assert_eq!(diag.cells_count, 4, "Cells count should be 4");
+            // 4 input cells and 4 adjacency cells
+            assert_eq!(diag.cells_count, 8, "Cells count should be 8");
 
             // guards_layer: 8
             // invalid_edges_layer: 2
@@ -608,6 +598,53 @@ mod tests {
         
     }
 
+    #[test]
+    fn test_topology_updates() {
+        let flags = [
+            GraphColoringFlags::default(),
+            GraphColoringFlags::new(true, false, false),
+            GraphColoringFlags::new(false, true, false),
+            GraphColoringFlags::new(false, false, true),
+            GraphColoringFlags::new(true, true, true),
+        ];
+
+        for flags in flags {
+            let mut graph = Graph::new();
+            graph.add_nodes((0..4).map(|_| Point::random()).collect());
+            graph.add_2d_edge(0, 2);
+            graph.add_2d_edge(0, 3);
+            graph.add_2d_edge(1, 2);
+            graph.add_2d_edge(1, 3);
+
+            let mut graph_coloring_comp = GraphColoringComp::new(Rc::new(graph), 4, flags);
+            graph_coloring_comp.create_computation_graph();
+            graph_coloring_comp.update_input_node(0, 1);
+            graph_coloring_comp.update_input_node(1, 1);
+            assert_eq!(graph_coloring_comp.get_result(), Some(-8));
+
+            // 0 and 1 share colour 1: -4 + 2 * 2 * 1 = 0, colour 0 stays -4
+            graph_coloring_comp.add_edge(1, 0);
+            assert_eq!(graph_coloring_comp.get_result(), Some(-4));
+
+            // adding an existing edge changes nothing
+            graph_coloring_comp.add_edge(0, 1);
+            assert_eq!(graph_coloring_comp.get_result(), Some(-4));
+
+            graph_coloring_comp.add_edge(2, 3);
+            assert_eq!(graph_coloring_comp.get_result(), Some(0));
+
+            graph_coloring_comp.remove_edge(0, 1);
+            graph_coloring_comp.remove_edge(0, 2);
+            assert_eq!(graph_coloring_comp.get_result(), Some(-4));
+
+            // moving a node over to another colour still sees the new topology
+            graph_coloring_comp.update_input_node(2, 1);
+            // colour 0: -1, colour 1: -9 + 2 * 3 * 1 = -3
+            assert_eq!(graph_coloring_comp.get_result(), Some(-4));
+            graph_coloring_comp.seal();
+        }
+    }
+
     #[test]
     fn test_new_color() {
         let mut graph = Graph::new();
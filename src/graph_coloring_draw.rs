@@ -1,4 +1,5 @@
 use std::collections::VecDeque;
+use std::env;
 use std::rc::Rc;
 use std::thread;
 use std::time::Duration;
@@ -8,6 +9,8 @@ use nannou::prelude::*;
 use crate::graph::{Graph, Point};
 use crate::graph_coloring::{Color, GraphColoring, ScoreCalcTypeGraphColoring};
 use crate::graph_coloring_comp::GraphColoringFlags;
+use crate::serialization::{self, Format, GraphColoringRun};
+use crate::viewport::Viewport;
 
 struct Model {
     graph: Rc<Graph>,
@@ -17,11 +20,10 @@ struct Model {
 
 fn draw_vertices(draw: &Draw, boundary: &Rect, model: &Model) {
     let nodes: &Vec<Point> = model.graph.get_nodes();
-    let graph_boundary = model.graph.get_boundary();
+    let viewport = Viewport::new(model.graph.get_bounding_box(), boundary, 20.0);
 
     for (i, node) in nodes.iter().enumerate() {
-        let x = map_range(node.x, graph_boundary.2, graph_boundary.3, boundary.left(), boundary.right());
-        let y = map_range(node.y, graph_boundary.0, graph_boundary.1, boundary.bottom(), boundary.top());
+        let (x, y) = viewport.map(node).into();
         draw.ellipse().x_y(x, y).radius(12.0).color(WHITE);
         let color = model.coloring[i].0;
         draw.text(&format!("{}", color))
@@ -32,10 +34,10 @@ fn draw_vertices(draw: &Draw, boundary: &Rect, model: &Model) {
 
 fn draw_edges(draw: &Draw, boundary: &Rect, model: &Model) {
     let nodes = model.graph.get_nodes();
-    let graph_boundary = model.graph.get_boundary();
+    let viewport = Viewport::new(model.graph.get_bounding_box(), boundary, 20.0);
 
     for (i, u) in nodes.iter().enumerate() {
-        for v in model.graph.get_adjacent_nodes(i as i32) {
+        for v in model.graph.adjacent_nodes(i as i32) {
             if v < i as i32 {
                 continue;
             }
@@ -47,21 +49,16 @@ fn draw_edges(draw: &Draw, boundary: &Rect, model: &Model) {
             };
 
             let v = &nodes[v as usize];
-            let ux = map_range(u.x, graph_boundary.2, graph_boundary.3, boundary.left(), boundary.right());
-            let uy = map_range(u.y, graph_boundary.0, graph_boundary.1, boundary.bottom(), boundary.top());
-            let vx = map_range(v.x, graph_boundary.2, graph_boundary.3, boundary.left(), boundary.right());
-            let vy = map_range(v.y, graph_boundary.0, graph_boundary.1, boundary.bottom(), boundary.top());
-
             draw.line()
-                .start(pt2(ux, uy))
-                .end(pt2(vx, vy))
+                .start(viewport.map(u))
+                .end(viewport.map(v))
                 .color(line_color);
         }
     }
 
 }
 
-fn model(_app: &App) -> Model {
+fn solve() -> GraphColoringRun {
     let mut graph = Graph::new();
     graph.fill_with_random_points(50);
     graph.fill_with_edges_stochastic(0.35);
@@ -76,10 +73,29 @@ fn model(_app: &App) -> Model {
     let starting_coloring = graph_coloring.coloring.clone();
     graph_coloring.graph_coloring();
 
+    GraphColoringRun {
+        graph: (*graph).clone(),
+        starting_coloring,
+        history: graph_coloring.history.clone(),
+    }
+}
+
+// GRAPH_COLORING_REPLAY=<file> replays a saved run instead of solving a new instance,
+// GRAPH_COLORING_SAVE=<file> archives the run, files ending with .json are saved as JSON
+fn model(_app: &App) -> Model {
+    let run = match env::var("GRAPH_COLORING_REPLAY") {
+        Ok(file) => serialization::load(&file, Format::from_path(&file)).unwrap(),
+        Err(_) => solve(),
+    };
+
+    if let Ok(file) = env::var("GRAPH_COLORING_SAVE") {
+        serialization::save(&run, &file, Format::from_path(&file)).unwrap();
+    }
+
     Model {
-        graph,
-        coloring: starting_coloring,
-        history: VecDeque::from(graph_coloring.history.clone()),
+        graph: Rc::new(run.graph),
+        coloring: run.starting_coloring,
+        history: VecDeque::from(run.history),
     }
 }
 
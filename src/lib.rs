@@ -3,7 +3,13 @@ pub mod graph;
 pub mod tsp;
 pub mod graph_coloring;
 pub mod graph_coloring_comp;
+pub mod tsplib;
+pub mod dimacs;
+pub mod generators;
+pub mod serialization;
+pub mod export;
 mod tsp_draw;
+mod viewport;
 mod tsp_comp;
 mod diagnostics;
 mod firewall_graph; 
\ No newline at end of file
@@ -8,6 +8,8 @@ mod graph_coloring_draw;
 mod graph_coloring_comp;
 mod diagnostics;
 mod firewall_graph;
+mod serialization;
+mod viewport;
 
 use dotenv::dotenv;
 use std::env;
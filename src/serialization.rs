@@ -0,0 +1,235 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{Graph, Weight};
+use crate::graph_coloring::Color;
+use crate::tsp::TspPath;
+
+// Saving and loading of instances and results, either as (pretty printed) JSON
+// which is easy to diff, or as a compact binary encoding (bincode)
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Binary,
+}
+
+impl Format {
+    // files ending with .json are JSON, everything else is binary
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Format {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Format::Json,
+            _ => Format::Binary,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SerializationError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Binary(bincode::Error),
+}
+
+impl fmt::Display for SerializationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializationError::Io(err) => write!(f, "io error: {}", err),
+            SerializationError::Json(err) => write!(f, "json error: {}", err),
+            SerializationError::Binary(err) => write!(f, "binary encoding error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SerializationError {}
+
+impl From<io::Error> for SerializationError {
+    fn from(err: io::Error) -> Self {
+        SerializationError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SerializationError {
+    fn from(err: serde_json::Error) -> Self {
+        SerializationError::Json(err)
+    }
+}
+
+impl From<bincode::Error> for SerializationError {
+    fn from(err: bincode::Error) -> Self {
+        SerializationError::Binary(err)
+    }
+}
+
+// the instance, the starting tour and every improved tour found by the search,
+// enough to replay the run in the visualiser
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "W: Weight")]
+pub struct TspRun<W: Weight = i32> {
+    pub graph: Graph<W>,
+    pub starting_path: TspPath,
+    pub history: Vec<TspPath>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphColoringRun {
+    pub graph: Graph,
+    pub starting_coloring: Vec<Color>,
+    pub history: Vec<Vec<Color>>,
+}
+
+pub fn to_bytes<T: Serialize>(value: &T, format: Format) -> Result<Vec<u8>, SerializationError> {
+    match format {
+        Format::Json => Ok(serde_json::to_vec_pretty(value)?),
+        Format::Binary => Ok(bincode::serialize(value)?),
+    }
+}
+
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8], format: Format) -> Result<T, SerializationError> {
+    match format {
+        Format::Json => Ok(serde_json::from_slice(bytes)?),
+        Format::Binary => Ok(bincode::deserialize(bytes)?),
+    }
+}
+
+pub fn save<T: Serialize, P: AsRef<Path>>(value: &T, path: P, format: Format) -> Result<(), SerializationError> {
+    fs::write(path, to_bytes(value, format)?)?;
+    Ok(())
+}
+
+pub fn load<T: DeserializeOwned, P: AsRef<Path>>(path: P, format: Format) -> Result<T, SerializationError> {
+    let bytes = fs::read(path)?;
+    from_bytes(&bytes, format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{Metric, Point};
+    use nannou::rand::rngs::StdRng;
+    use nannou::rand::SeedableRng;
+
+    fn assert_same_graph<W: Weight>(a: &Graph<W>, b: &Graph<W>) {
+        assert_eq!(a.get_number_of_nodes(), b.get_number_of_nodes());
+        assert_eq!(a.get_boundary(), b.get_boundary());
+        assert_eq!(a.is_directed(), b.is_directed());
+        assert_eq!(a.get_metric(), b.get_metric());
+        for (p, q) in a.get_nodes().iter().zip(b.get_nodes()) {
+            assert_eq!((p.x, p.y), (q.x, q.y));
+        }
+        assert_eq!(a.edges.len(), b.edges.len());
+        for (e, f) in a.edges.iter().zip(b.edges.iter()) {
+            assert_eq!((e.p1, e.p2), (f.p1, f.p2));
+            assert_eq!(e.weight.to_bits(), f.weight.to_bits());
+        }
+        assert_eq!(a.edges_lookup, b.edges_lookup);
+    }
+
+    #[test]
+    fn test_graph_round_trip() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut graph = Graph::new();
+        graph.fill_with_random_points_rng(12, &mut rng);
+        graph.fill_with_edges_stochastic_rng(0.4, &mut rng);
+
+        let mut exact: Graph<f64> = Graph::default();
+        exact.set_directed(true);
+        exact.set_metric(Metric::Manhattan);
+        exact.add_nodes(vec![Point::new(0.5, 1.0), Point::new(2.0, 3.25)]);
+        exact.add_edge(0, 1, 0.1);
+        exact.add_edge(1, 0, 2.5);
+
+        for format in [Format::Json, Format::Binary] {
+            let bytes = to_bytes(&graph, format).unwrap();
+            assert_same_graph(&graph, &from_bytes(&bytes, format).unwrap());
+
+            let bytes = to_bytes(&exact, format).unwrap();
+            assert_same_graph(&exact, &from_bytes(&bytes, format).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_runs_round_trip() {
+        let mut graph = Graph::new();
+        graph.fill_with_random_points(4);
+        graph.fill_with_edges_full();
+
+        let tsp_run = TspRun {
+            graph: graph.clone(),
+            starting_path: vec![0, 2, 1, 3],
+            history: vec![vec![0, 1, 2, 3], vec![0, 1, 3, 2]],
+        };
+        let coloring_run = GraphColoringRun {
+            graph,
+            starting_coloring: vec![Color(0); 4],
+            history: vec![vec![Color(1), Color(0), Color(0), Color(0)]],
+        };
+
+        for format in [Format::Json, Format::Binary] {
+            let loaded: TspRun = from_bytes(&to_bytes(&tsp_run, format).unwrap(), format).unwrap();
+            assert_same_graph(&tsp_run.graph, &loaded.graph);
+            assert_eq!(loaded.starting_path, tsp_run.starting_path);
+            assert_eq!(loaded.history, tsp_run.history);
+
+            let loaded: GraphColoringRun = from_bytes(&to_bytes(&coloring_run, format).unwrap(), format).unwrap();
+            assert_same_graph(&coloring_run.graph, &loaded.graph);
+            assert_eq!(loaded.starting_coloring, coloring_run.starting_coloring);
+            assert_eq!(loaded.history, coloring_run.history);
+        }
+
+        assert_eq!(String::from_utf8(to_bytes(&vec![Color(2), Color(0)], Format::Json).unwrap()).unwrap(), "[\n  2,\n  0\n]");
+    }
+
+    // the lib and the bin run this test at the same time, the process id keeps their files apart
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("serialization_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let mut graph = Graph::new();
+        graph.fill_with_random_points(5);
+        graph.fill_with_edges_full();
+        let history: Vec<TspPath> = vec![vec![4, 3, 2, 1, 0]];
+
+        for name in ["save_and_load.json", "save_and_load.bin"] {
+            let path = temp_path(name);
+            let format = Format::from_path(&path);
+
+            save(&graph, &path, format).unwrap();
+            let loaded: Graph = load(&path, format).unwrap();
+            assert_same_graph(&graph, &loaded);
+
+            save(&history, &path, format).unwrap();
+            assert_eq!(load::<Vec<TspPath>, _>(&path, format).unwrap(), history);
+            fs::remove_file(&path).unwrap();
+        }
+
+        assert_eq!(Format::from_path("run.JSON"), Format::Json);
+        assert_eq!(Format::from_path("run.bin"), Format::Binary);
+        assert!(matches!(load::<Graph, _>(temp_path("missing.json"), Format::Json), Err(SerializationError::Io(_))));
+    }
+
+    #[test]
+    fn test_invalid_graph() {
+        let json = r#"{
+            "nodes": [{ "x": 0.0, "y": 0.0 }, { "x": 1.0, "y": 1.0 }],
+            "edges": [{ "p1": 0, "p2": 1, "weight": 1 }, { "p1": 1, "p2": 0, "weight": 1 }],
+            "boundary": [0.0, 100.0, 0.0, 100.0],
+            "directed": false
+        }"#;
+        let err = from_bytes::<Graph>(json.as_bytes(), Format::Json).unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{}", err);
+
+        let out_of_range = json.replace(r#""p1": 1, "p2": 0"#, r#""p1": 1, "p2": 5"#);
+        assert!(from_bytes::<Graph>(out_of_range.as_bytes(), Format::Json).is_err());
+
+        let directed = json.replace("false", "true");
+        assert_eq!(from_bytes::<Graph>(directed.as_bytes(), Format::Json).unwrap().edges.len(), 2);
+    }
+}
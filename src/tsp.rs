@@ -1,8 +1,17 @@
+use std::collections::{HashSet, VecDeque};
 use std::rc::Rc;
 
-use crate::graph::{Graph, PointId};
+pub mod construction;
+pub mod exact;
+pub mod lower_bound;
+
+use crate::graph::kdtree::Candidates;
+use crate::graph::{Graph, PointId, Weight, EPS};
 use crate::tsp_comp::TspComp;
-use nannou::rand;
+use construction::Construction;
+use nannou::rand::rngs::StdRng;
+use nannou::rand::seq::SliceRandom;
+use nannou::rand::{Rng, SeedableRng};
 
 pub type TspPath = Vec<PointId>;
 
@@ -12,46 +21,179 @@ pub enum ScoreCalcTypeTSP {
     Incremental
 }
 
-pub struct Tsp {
-    graph:  Rc<Graph>,
-    history: Vec<TspPath>,
-    path: TspPath,
-    score_calc_type: ScoreCalcTypeTSP,
-    computation_graph: Option<TspComp>,
+// Moves tried by the local search. Moves that reverse a part of the tour change its length
+// on asymmetric instances, directed graphs therefore only support the moves without reversal:
+// or-opt and the 3-opt segment swap.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Neighborhood {
+    TwoOpt,
+    // relocates a segment of 1 to 3 cities, on undirected graphs also reversed
+    OrOpt,
+    // a 2-opt pass followed by an or-opt pass in every round
+    TwoOptOrOpt,
+    // all 7 ways of reconnecting the tour after removing 3 edges, O(n^3) per pass
+    ThreeOpt,
+    // chains of up to max_depth sequential 2-opt moves, uses the candidate lists if set
+    LinKernighan { max_depth: usize },
 }
 
-fn unsafe_create_static_pointer(al: Vec<Vec<i32>>) -> &'static Vec<Vec<i32>> {
-    static mut AL: *const Vec<Vec<i32>> = std::ptr::null();
+// A 3-opt move removes the edges leaving path[i], path[j] and path[k], i < j < k, and joins
+// the segments path[i + 1..=j] and path[j + 1..=k] again, possibly swapped and reversed.
+// The 7 combinations other than the current tour are the reconnection cases,
+// the first three only replace 2 edges and are 2-opt moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Reconnection {
+    swap: bool,
+    reverse_first: bool,
+    reverse_second: bool,
+}
+
+const RECONNECTIONS: [Reconnection; 7] = [
+    Reconnection { swap: false, reverse_first: true, reverse_second: false },
+    Reconnection { swap: false, reverse_first: false, reverse_second: true },
+    Reconnection { swap: true, reverse_first: true, reverse_second: true },
+    Reconnection { swap: false, reverse_first: true, reverse_second: true },
+    Reconnection { swap: true, reverse_first: true, reverse_second: false },
+    Reconnection { swap: true, reverse_first: false, reverse_second: true },
+    Reconnection { swap: true, reverse_first: false, reverse_second: false },
+];
 
-    unsafe {
-        AL = Box::into_raw(Box::new(al));
+impl Reconnection {
+    fn reverses(&self) -> bool {
+        self.reverse_first || self.reverse_second
     }
+}
 
-    unsafe { &*AL }
+// Which of the improving moves of a sweep the local search applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PivotingRule {
+    // every improving move as soon as it is found
+    #[default]
+    FirstImprovement,
+    // only the best move of the sweep, in Slow and Incremental mode every move is applied,
+    // measured and taken back before the winner is applied again
+    BestImprovement,
+    // first improvement, but every sweep visits the first position of the moves in a new random order
+    RandomOrderFirst,
 }
 
-impl Tsp {
-    pub fn new(graph: Rc<Graph>, score_calc_type: ScoreCalcTypeTSP) -> Tsp {
+// a move of the local search, given by positions in the current path
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Move {
+    // reverses path[i + 1..=j]
+    TwoOpt(usize, usize),
+    // relocates path[i..i + len] after path[j]
+    OrOpt { i: usize, len: usize, j: usize, reversed: bool },
+    ThreeOpt(usize, usize, usize, Reconnection),
+}
+
+pub struct Tsp<W: Weight = i32> {
+    graph:  Rc<Graph<W>>,
+    // dense copy of the weights, pairs without an edge cost the missing edge penalty of the graph.
+    // Takes O(n^2) memory in every mode, also for sparse graphs, in exchange for O(1) lookups
+    // in the move evaluation and a shared input for the incremental computation.
+    distances: Rc<Vec<Vec<W>>>,
+    penalty: W,
+    // ordered pairs without an edge, the cells that hold the penalty
+    missing: HashSet<(PointId, PointId)>,
+    history: Vec<TspPath>,
+    path: TspPath,
+    score_calc_type: ScoreCalcTypeTSP,
+    neighborhood: Neighborhood,
+    pivoting_rule: PivotingRule,
+    // 2-opt keeps a queue of active cities instead of sweeping over all of them
+    dont_look_bits: bool,
+    // moves measured by the last run of the search
+    evaluations: usize,
+    computation_graph: Option<TspComp<W>>,
+    // if set, 2-opt only tries moves that add an edge from a node to one of its candidates
+    candidates: Option<Vec<Vec<PointId>>>,
+    rng: StdRng,
+}
+
+// a move is accepted only if it shortens the tour by more than a rounding error,
+// otherwise floating point weights could make the search cycle between equal tours
+fn improves<W: Weight>(delta: W) -> bool {
+    delta.to_f64() < -EPS
+}
+
+impl<W: Weight> Tsp<W> {
+    // builds the n x n distance matrix, so O(n^2) time and memory regardless of the number of edges
+    pub fn new(graph: Rc<Graph<W>>, score_calc_type: ScoreCalcTypeTSP) -> Tsp<W> {
         let number_of_nodes = graph.get_number_of_nodes();
+        let penalty = Self::penalty_with_headroom(&graph, graph.get_missing_edge_penalty());
+        let distances = Rc::new(graph.get_distance_matrix(penalty));
+        let missing = (0..number_of_nodes as PointId)
+            .flat_map(|u| (0..number_of_nodes as PointId).map(move |v| (u, v)))
+            .filter(|&(u, v)| u != v && graph.get_edge_from_lookup(u, v).is_none())
+            .collect();
 
+        let neighborhood = if graph.is_directed() { Neighborhood::OrOpt } else { Neighborhood::TwoOpt };
         let computation_graph = match score_calc_type {
             ScoreCalcTypeTSP::Incremental => {
-                let al = graph.get_raw_adjacency_list();
-                let static_al = unsafe_create_static_pointer(al);
-                Some(TspComp::new(static_al, number_of_nodes))
+                Some(TspComp::new(Rc::clone(&distances), number_of_nodes))
             },
             _ => None
         };
 
         Tsp {
             graph,
+            distances,
+            penalty,
+            missing,
             path: Vec::new(),
             history: Vec::new(),
             computation_graph,
-            score_calc_type
+            score_calc_type,
+            neighborhood,
+            pivoting_rule: PivotingRule::default(),
+            dont_look_bits: false,
+            evaluations: 0,
+            candidates: None,
+            rng: StdRng::from_entropy(),
         }
     }
 
+    // makes the random choices of the search (starting path) reproducible
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    pub fn set_neighborhood(&mut self, neighborhood: Neighborhood) {
+        let keeps_direction = matches!(neighborhood, Neighborhood::OrOpt | Neighborhood::ThreeOpt);
+        assert!(!self.graph.is_directed() || keeps_direction, "Directed graphs only support or-opt and 3-opt");
+        Self::assert_supported(neighborhood, self.pivoting_rule);
+        self.neighborhood = neighborhood;
+    }
+
+    pub fn set_pivoting_rule(&mut self, pivoting_rule: PivotingRule) {
+        Self::assert_supported(self.neighborhood, pivoting_rule);
+        self.pivoting_rule = pivoting_rule;
+    }
+
+    // only used by 2-opt, also as part of TwoOptOrOpt
+    pub fn set_dont_look_bits(&mut self, dont_look_bits: bool) {
+        self.dont_look_bits = dont_look_bits;
+    }
+
+    // a Lin-Kernighan chain already keeps its best step, its moves are not compared with each other
+    fn assert_supported(neighborhood: Neighborhood, pivoting_rule: PivotingRule) {
+        let lin_kernighan = matches!(neighborhood, Neighborhood::LinKernighan { .. });
+        assert!(!lin_kernighan || pivoting_rule != PivotingRule::BestImprovement, "Lin-Kernighan does not support best improvement");
+    }
+
+    // restricts 2-opt to candidate edges, the lists are built from the node coordinates
+    pub fn set_candidates(&mut self, candidates: Candidates) {
+        self.candidates = Some(self.graph.get_candidate_lists(candidates));
+    }
+
+    pub fn set_candidate_lists(&mut self, lists: Vec<Vec<PointId>>) {
+        assert_eq!(lists.len(), self.graph.get_number_of_nodes(), "Every node needs a candidate list");
+        self.candidates = Some(lists);
+    }
+
     pub fn set_starting_path(&mut self, path: TspPath) {
         let n = self.graph.get_number_of_nodes() as i32;
         if let Some(comp_graph) = &mut self.computation_graph {
@@ -67,7 +209,7 @@ impl Tsp {
         let mut vertecies = (0..n).collect::<Vec<i32>>();
 
         while !vertecies.is_empty() {
-            let next_vertex = rand::random_range(0, vertecies.len() as i32);
+            let next_vertex = self.rng.gen_range(0..vertecies.len() as i32);
             path.push(vertecies.swap_remove(next_vertex as usize));
         }
 
@@ -75,19 +217,39 @@ impl Tsp {
         self.path.clone()
     }
 
-    fn calculate_path_length_naive(&self) -> i32 {
-        let mut length = 0;
+    // starting path built by a construction heuristic, nearest neighbour starts at a random node
+    pub fn construct_starting_path(&mut self, construction: Construction) -> TspPath {
+        let graph = Rc::clone(&self.graph);
+        let path = match construction {
+            Construction::Random => return self.generate_starting_path(),
+            Construction::NearestNeighbor => {
+                let start = self.rng.gen_range(0..graph.get_number_of_nodes().max(1) as PointId);
+                construction::nearest_neighbor(&graph, start)
+            },
+            Construction::GreedyEdge => construction::greedy_edge(&graph),
+            Construction::CheapestInsertion => construction::cheapest_insertion(&graph),
+            Construction::FarthestInsertion => construction::farthest_insertion(&graph),
+            Construction::SpaceFillingCurve => construction::space_filling_curve(&graph),
+            Construction::MstDoubleTree => construction::mst_double_tree(&graph),
+        };
+
+        self.set_starting_path(path);
+        self.path.clone()
+    }
+
+    fn calculate_path_length_naive(&self) -> W {
+        let mut length = W::default();
         let n = self.path.len();
         for i in 0..(self.path.len()) {
             let u = self.path[i];
             let v = self.path[(i + 1) % n];
-            length += self.graph.get_edge_from_lookup(u, v).unwrap().weight;
+            length = length + self.weight(u, v);
         }
 
         length
     }
 
-    fn calculate_path_length(&self) -> i32 {
+    fn calculate_path_length(&self) -> W {
         match &self.score_calc_type {
             ScoreCalcTypeTSP::Incremental => {
                 self.computation_graph.as_ref().unwrap().get_result()
@@ -115,73 +277,662 @@ impl Tsp {
         }
     }
 
-    fn finish(&mut self) {
+    fn update_positions(&mut self, from: usize, to: usize) {
+        if let Some(comp_graph) = &mut self.computation_graph {
+            comp_graph.update_input_nodes((from..=to).map(|k| (k, self.path[k])).collect());
+        }
+    }
+
+    // positions [from, to] changed by moving the segment path[i..i + len] right after path[j]
+    // and the position at which the segment starts afterwards
+    fn segment_move_range(i: usize, len: usize, j: usize) -> (usize, usize, usize) {
+        if j >= i + len {
+            (i, j, j + 1 - len)
+        } else {
+            (j + 1, i + len - 1, j + 1)
+        }
+    }
+
+    // moves the segment path[i..i + len] right after path[j], optionally reversing it,
+    // j must lie outside of the segment and must not be its predecessor
+    fn move_segment(&mut self, i: usize, len: usize, j: usize, reversed: bool) {
+        let (from, to, start) = Self::segment_move_range(i, len, j);
+        if j >= i + len {
+            self.path[from..=to].rotate_left(len);
+        } else {
+            self.path[from..=to].rotate_right(len);
+        }
+        if reversed {
+            self.path[start..start + len].reverse();
+        }
+        self.update_positions(from, to);
+    }
+
+    fn undo_move_segment(&mut self, i: usize, len: usize, j: usize, reversed: bool) {
+        let (from, to, start) = Self::segment_move_range(i, len, j);
+        if reversed {
+            self.path[start..start + len].reverse();
+        }
+        if j >= i + len {
+            self.path[from..=to].rotate_right(len);
+        } else {
+            self.path[from..=to].rotate_left(len);
+        }
+        self.update_positions(from, to);
+    }
+
+    fn weight(&self, u: PointId, v: PointId) -> W {
+        self.distances[u as usize][v as usize]
+    }
+
+    // length change of the move, computed from the changed edges
+    fn fast_delta(&self, mv: Move) -> W {
+        let n = self.path.len();
+        match mv {
+            Move::TwoOpt(i, j) => {
+                let e1 = self.weight(self.path[i], self.path[i+1]);
+                let e2 = self.weight(self.path[j], self.path[(j+1)%n]);
+                let ne1 = self.weight(self.path[i], self.path[j]);
+                let ne2 = self.weight(self.path[i+1], self.path[(j+1)%n]);
+
+                (ne1 + ne2) - (e1 + e2)
+            },
+            Move::OrOpt { i, len, j, reversed } => {
+                let prev = self.path[(i + n - 1) % n];
+                let first = self.path[i];
+                let last = self.path[i + len - 1];
+                let next = self.path[(i + len) % n];
+                let a = self.path[j];
+                let b = self.path[(j + 1) % n];
+
+                // a reversed segment is entered at its last city
+                let (head, tail) = if reversed { (last, first) } else { (first, last) };
+                let removed = self.weight(prev, first) + self.weight(last, next) + self.weight(a, b);
+                let added = self.weight(prev, next) + self.weight(a, head) + self.weight(tail, b);
+                added - removed
+            },
+            Move::ThreeOpt(i, j, k, reconnection) => {
+                let (a, b, c) = (self.path[i], self.path[i + 1], self.path[j]);
+                let (d, e, f) = (self.path[j + 1], self.path[k], self.path[(k + 1) % n]);
+
+                let first = if reconnection.reverse_first { (c, b) } else { (b, c) };
+                let second = if reconnection.reverse_second { (e, d) } else { (d, e) };
+                let (x, y) = if reconnection.swap { (second, first) } else { (first, second) };
+
+                let removed = self.weight(a, b) + self.weight(c, d) + self.weight(e, f);
+                let added = self.weight(a, x.0) + self.weight(x.1, y.0) + self.weight(y.1, f);
+                added - removed
+            },
+        }
+    }
+
+    fn apply_move(&mut self, mv: Move) {
+        match mv {
+            Move::TwoOpt(i, j) => self.swap_edges(i, j),
+            Move::OrOpt { i, len, j, reversed } => self.move_segment(i, len, j, reversed),
+            Move::ThreeOpt(i, j, k, reconnection) => self.reconnect(i, j, k, reconnection),
+        }
+    }
+
+    // the part of the path a 3-opt move rewrites, needed to take the move back
+    fn saved_block(&self, mv: Move) -> Vec<PointId> {
+        match mv {
+            Move::ThreeOpt(i, _, k, _) => self.path[i + 1..=k].to_vec(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn undo_move(&mut self, mv: Move, block: &[PointId]) {
+        match mv {
+            // 2-opt moves are their own inverse
+            Move::TwoOpt(i, j) => self.swap_edges(i, j),
+            Move::OrOpt { i, len, j, reversed } => self.undo_move_segment(i, len, j, reversed),
+            Move::ThreeOpt(i, ..) => self.restore_block(i + 1, block),
+        }
+    }
+
+    // applies the move if that shortens the tour
+    fn try_move(&mut self, mv: Move, best_length: &mut W, history: &mut Vec<TspPath>) -> bool {
+        self.evaluations += 1;
+        match &self.score_calc_type {
+            ScoreCalcTypeTSP::Fast => {
+                let delta = self.fast_delta(mv);
+
+                if improves(delta) {
+                    self.apply_move(mv);
+                    *best_length = *best_length + delta;
+                    history.push(self.path.clone());
+                    return true;
+                }
+            },
+            _ => {
+                let block = self.saved_block(mv);
+                self.apply_move(mv);
+                let new_length = self.calculate_path_length();
+
+                if improves(new_length - *best_length) {
+                    *best_length = new_length;
+                    history.push(self.path.clone());
+                    return true;
+                }
+
+                self.undo_move(mv, &block);
+            }
+        }
+
+        false
+    }
+
+    // length change of the move without keeping it, Slow and Incremental have to apply
+    // the move to measure it and take it back afterwards
+    fn move_delta(&mut self, mv: Move, length: W) -> W {
+        self.evaluations += 1;
+        match &self.score_calc_type {
+            ScoreCalcTypeTSP::Fast => self.fast_delta(mv),
+            _ => {
+                let block = self.saved_block(mv);
+                self.apply_move(mv);
+                let delta = self.calculate_path_length() - length;
+                self.undo_move(mv, &block);
+                delta
+            }
+        }
+    }
+
+    // Under first improvement an improving move is applied right away. Under best improvement
+    // the move is only measured and kept in `best_move` if it beats the moves seen before,
+    // apply_best_move applies the winner at the end of the sweep.
+    fn consider(&mut self, mv: Move, best_length: &mut W, history: &mut Vec<TspPath>, best_move: &mut Option<(W, Move)>) -> bool {
+        if self.pivoting_rule != PivotingRule::BestImprovement {
+            return self.try_move(mv, best_length, history);
+        }
+
+        let delta = self.move_delta(mv, *best_length);
+        if improves(delta) && best_move.is_none_or(|(best, _)| delta < best) {
+            *best_move = Some((delta, mv));
+        }
+        false
+    }
+
+    fn apply_best_move(&mut self, best_move: Option<(W, Move)>, best_length: &mut W, history: &mut Vec<TspPath>) -> bool {
+        let Some((delta, mv)) = best_move else {
+            return false;
+        };
+
+        self.apply_move(mv);
+        *best_length = match &self.score_calc_type {
+            ScoreCalcTypeTSP::Fast => *best_length + delta,
+            _ => self.calculate_path_length(),
+        };
+        history.push(self.path.clone());
+        true
+    }
+
+    // order of the first position of the moves in a sweep
+    fn scan_order(&mut self, positions: std::ops::Range<usize>) -> Vec<usize> {
+        let mut order = positions.collect::<Vec<usize>>();
+        if self.pivoting_rule == PivotingRule::RandomOrderFirst {
+            order.shuffle(&mut self.rng);
+        }
+        order
+    }
+
+    // Or-opt: every segment of 1 to 3 cities is tried after every other city. Segments are only
+    // reversed on undirected graphs, without reversal the move is valid for asymmetric instances.
+    fn or_opt_pass(&mut self, best_length: &mut W, history: &mut Vec<TspPath>) -> bool {
+        let n = self.path.len();
+        let directed = self.graph.is_directed();
+        let mut improved = false;
+        let mut best_move = None;
+
+        for len in 1..=3 {
+            if len + 2 > n {
+                break;
+            }
+
+            for i in self.scan_order(0..n - len + 1) {
+                for j in 0..n {
+                    // j has to be outside of the segment and must not be its predecessor
+                    let offset = (j + n - i) % n;
+                    if offset < len || offset == n - 1 {
+                        continue;
+                    }
+
+                    // a single city looks the same in both directions
+                    if self.consider(Move::OrOpt { i, len, j, reversed: false }, best_length, history, &mut best_move)
+                        || (len > 1 && !directed && self.consider(Move::OrOpt { i, len, j, reversed: true }, best_length, history, &mut best_move)) {
+                        improved = true;
+                    }
+                }
+            }
+        }
+
+        self.apply_best_move(best_move, best_length, history) || improved
+    }
+
+    // Edits of the graph are applied to a private copy if the graph is shared,
+    // the incremental computation receives them as input changes and is not rebuilt
+    pub fn add_edge(&mut self, u: PointId, v: PointId, weight: W) {
+        Rc::make_mut(&mut self.graph).add_edge(u, v, weight);
+        self.refresh_distances(u, v);
+    }
+
+    pub fn remove_edge(&mut self, u: PointId, v: PointId) {
+        Rc::make_mut(&mut self.graph).remove_edge(u, v);
+        self.refresh_distances(u, v);
+    }
+
+    pub fn set_edge_weight(&mut self, u: PointId, v: PointId, weight: W) {
+        Rc::make_mut(&mut self.graph).set_edge_weight(u, v, weight);
+        self.refresh_distances(u, v);
+    }
+
+    // the penalty is chosen with headroom, twice the bound of the graph, so that most edits
+    // do not have to touch the cells of missing edges. The headroom stops at the cap of the graph,
+    // a tour of n missing edges has to fit in W.
+    fn penalty_with_headroom(graph: &Graph<W>, bound: W) -> W {
+        let cap = graph.get_max_missing_edge_penalty();
+        W::from_f64((2.0 * bound.to_f64()).min(cap.to_f64()))
+    }
+
+    // only the cells of the edited pair change, unless the new weight breaks the bound of the penalty,
+    // then the cells of the missing edges are raised, which costs O(missing edges)
+    fn refresh_distances(&mut self, u: PointId, v: PointId) {
+        let n = self.graph.get_number_of_nodes();
+        let pairs = if self.graph.is_directed() { vec![(u, v)] } else { vec![(u, v), (v, u)] };
+
+        let mut updates = Vec::new();
+        for (a, b) in pairs {
+            let weight = match self.graph.get_edge_from_lookup(a, b) {
+                Some(edge) => {
+                    self.missing.remove(&(a, b));
+                    edge.weight
+                },
+                None => {
+                    self.missing.insert((a, b));
+                    self.penalty
+                }
+            };
+            updates.push((a as usize, b as usize, weight));
+        }
+
+        // a missing edge has to stay more expensive than any tour without one,
+        // so a tour of n edges of the new weight
+        if let Some(edge) = self.graph.get_edge_from_lookup(u, v) {
+            let cap = self.graph.get_max_missing_edge_penalty();
+            if n as f64 * edge.weight.to_f64() >= self.penalty.to_f64() && self.penalty < cap {
+                let bound = (n as f64 * edge.weight.to_f64() + 1.0).min(cap.to_f64());
+                self.penalty = Self::penalty_with_headroom(&self.graph, W::from_f64(bound));
+                updates.extend(self.missing.iter().map(|&(a, b)| (a as usize, b as usize, self.penalty)));
+            }
+        }
+
+        let distances = Rc::make_mut(&mut self.distances);
+        for &(a, b, weight) in &updates {
+            distances[a][b] = weight;
+        }
+
+        if let Some(comp_graph) = &mut self.computation_graph {
+            comp_graph.update_distances(updates);
+        }
+    }
+
+    // 2-opt over candidate edges only: for a node u and a candidate c the new edge (u, c)
+    // either replaces the edges leaving u and c or the edges entering them
+    fn candidate_two_opt_pass(&mut self, best_length: &mut W, history: &mut Vec<TspPath>) -> bool {
+        let n = self.path.len();
+        let candidates = self.candidates.take().unwrap();
+        let mut position = vec![0; n];
+        for (k, &u) in self.path.iter().enumerate() {
+            position[u as usize] = k;
+        }
+
+        let mut improved = false;
+        let mut best_move = None;
+        for u in self.scan_order(0..n) {
+            for &c in &candidates[u] {
+                for predecessors in [false, true] {
+                    let (a, b) = (position[u], position[c as usize]);
+                    let (a, b) = if predecessors { ((a + n - 1) % n, (b + n - 1) % n) } else { (a, b) };
+                    let (i, j) = (a.min(b), a.max(b));
+                    if j < i + 2 {
+                        continue;
+                    }
+
+                    if self.consider(Move::TwoOpt(i, j), best_length, history, &mut best_move) {
+                        improved = true;
+                        for k in i + 1..=j {
+                            position[self.path[k] as usize] = k;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.candidates = Some(candidates);
+        self.apply_best_move(best_move, best_length, history) || improved
+    }
+
+    // seals the incremental computation and prints its traces with the traces feature,
+    // no further moves or edits are possible afterwards
+    pub fn finish(&mut self) {
         if let Some(comp_graph) = &mut self.computation_graph {
             comp_graph.seal();
         }
     }
 
-    pub fn tsp(&mut self) -> Result<i32, ()> {
-        let mut best_length = self.calculate_path_length();
-        let n = self.path.len() as usize;
-        let mut improved = true;
+    // rewrites path[i + 1..=k] as described by the reconnection
+    fn reconnect(&mut self, i: usize, j: usize, k: usize, reconnection: Reconnection) {
+        let mut first = self.path[i + 1..=j].to_vec();
+        let mut second = self.path[j + 1..=k].to_vec();
+        if reconnection.reverse_first {
+            first.reverse();
+        }
+        if reconnection.reverse_second {
+            second.reverse();
+        }
 
-        let mut history: Vec<TspPath> = Vec::new();
+        let block = if reconnection.swap { [second, first].concat() } else { [first, second].concat() };
+        self.restore_block(i + 1, &block);
+    }
 
-        while improved {
-            improved = false;
-            for i in 0..n-1 {
-                for j in i+2..n {
-                    match &self.score_calc_type {
-                        ScoreCalcTypeTSP::Fast => {
-                            let e1 = self.graph.get_edge_from_lookup(self.path[i], self.path[i+1]).unwrap().weight;
-                            let e2 = self.graph.get_edge_from_lookup(self.path[j], self.path[(j+1)%n]).unwrap().weight;
-                            let ne1 = self.graph.get_edge_from_lookup(self.path[i], self.path[j]).unwrap().weight;
-                            let ne2 = self.graph.get_edge_from_lookup(self.path[i+1], self.path[(j+1)%n]).unwrap().weight;
-                            
-                            let delta = (ne1 + ne2) - (e1 + e2);
-
-                            if delta < 0 {
-                                self.swap_edges(i, j);
-                                improved = true;
-                                best_length += delta;
-                                history.push(self.path.clone());
-                            }
-                        },
-                        _ => {
-                            self.swap_edges(i, j);
-                            let new_length = self.calculate_path_length();
-
-                            if new_length < best_length {
-                                best_length = new_length;
-                                improved = true;
-                                history.push(self.path.clone());
-                            } else {
-                                // reverse
-                                self.swap_edges(i, j);
-                            }
+    fn restore_block(&mut self, from: usize, block: &[PointId]) {
+        self.path[from..from + block.len()].copy_from_slice(block);
+        self.update_positions(from, from + block.len() - 1);
+    }
+
+    fn three_opt_pass(&mut self, best_length: &mut W, history: &mut Vec<TspPath>) -> bool {
+        let n = self.path.len();
+        let directed = self.graph.is_directed();
+        let mut improved = false;
+        let mut best_move = None;
+
+        for i in self.scan_order(0..n) {
+            for j in i + 1..n {
+                for k in j + 1..n {
+                    for reconnection in RECONNECTIONS {
+                        if directed && reconnection.reverses() {
+                            continue;
+                        }
+
+                        // after an improvement the segments have changed, the next cases start from the new tour
+                        if self.consider(Move::ThreeOpt(i, j, k, reconnection), best_length, history, &mut best_move) {
+                            improved = true;
                         }
                     }
                 }
             }
         }
 
+        self.apply_best_move(best_move, best_length, history) || improved
+    }
+
+    // Lin–Kernighan style variable-depth search built from sequential 2-opt moves.
+    // A chain starts by removing the tour edge (t1, t2). Every step adds an edge (t2, t3) and removes
+    // the edge (t3, t4) that turns the tour into one with the closing edge (t4, t1), t4 is the next t2.
+    // Steps are only taken while the gain without the closing edge is positive and the best tour
+    // along the chain is kept. Every first t3 is tried, deeper steps pick the best one greedily.
+
+    // position of the tour edge between u and v, the edge leaves path[position]
+    fn tour_edge_position(&self, position: &[usize], u: PointId, v: PointId) -> usize {
+        let n = self.path.len();
+        let p = position[u as usize];
+        if self.path[(p + 1) % n] == v { p } else { position[v as usize] }
+    }
+
+    fn lin_kernighan_step(&mut self, position: &mut [usize], edges: [(PointId, PointId); 2]) -> (usize, usize) {
+        let a = self.tour_edge_position(position, edges[0].0, edges[0].1);
+        let b = self.tour_edge_position(position, edges[1].0, edges[1].1);
+        let (i, j) = (a.min(b), a.max(b));
+
+        self.swap_edges(i, j);
+        for k in i + 1..=j {
+            position[self.path[k] as usize] = k;
+        }
+        (i, j)
+    }
+
+    // 2-opt moves are their own inverse
+    fn undo_lin_kernighan_steps(&mut self, position: &mut [usize], steps: &[(usize, usize)]) {
+        for &(i, j) in steps.iter().rev() {
+            self.swap_edges(i, j);
+            for k in i + 1..=j {
+                position[self.path[k] as usize] = k;
+            }
+        }
+    }
+
+    // feasible (gain after the step, t3, t4), the most promising first
+    fn lin_kernighan_choices(&self, position: &[usize], t1: PointId, t2: PointId, gain: W, used: &[PointId]) -> Vec<(W, PointId, PointId)> {
+        let n = self.path.len();
+        let forward = self.path[(position[t1 as usize] + 1) % n] == t2;
+        let candidates = match &self.candidates {
+            Some(lists) => lists[t2 as usize].clone(),
+            None => (0..n as PointId).collect(),
+        };
+
+        let mut choices = candidates
+            .into_iter()
+            .filter(|&t3| t3 != t1 && t3 != t2 && !used.contains(&t3))
+            .filter_map(|t3| {
+                let p3 = position[t3 as usize];
+                let t4 = if forward { self.path[(p3 + n - 1) % n] } else { self.path[(p3 + 1) % n] };
+                let partial = gain - self.weight(t2, t3);
+                // t4 == t2 means that (t2, t3) is already a tour edge
+                if t4 == t2 || partial <= W::default() {
+                    return None;
+                }
+                Some((partial + self.weight(t3, t4), t3, t4))
+            })
+            .collect::<Vec<(W, PointId, PointId)>>();
+
+        choices.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap().then(a.1.cmp(&b.1)));
+        choices
+    }
+
+    fn lin_kernighan_length(&mut self, start_length: W, gain: W, t1: PointId, t2: PointId) -> W {
+        self.evaluations += 1;
+        match &self.score_calc_type {
+            ScoreCalcTypeTSP::Fast => start_length - (gain - self.weight(t2, t1)),
+            _ => self.calculate_path_length(),
+        }
+    }
+
+    fn try_lin_kernighan(&mut self, t1: PointId, t2: PointId, max_depth: usize, position: &mut [usize], best_length: &mut W, history: &mut Vec<TspPath>) -> bool {
+        let start_length = *best_length;
+
+        for (first_gain, first_t3, first_t4) in self.lin_kernighan_choices(position, t1, t2, self.weight(t1, t2), &[]) {
+            let mut steps = vec![self.lin_kernighan_step(position, [(t1, t2), (first_t3, first_t4)])];
+            let mut used = vec![first_t3];
+            let (mut gain, mut last) = (first_gain, first_t4);
+            let mut best = (self.lin_kernighan_length(start_length, gain, t1, last), 1);
+
+            while steps.len() < max_depth {
+                let Some(&(next_gain, t3, t4)) = self.lin_kernighan_choices(position, t1, last, gain, &used).first() else {
+                    break;
+                };
+
+                steps.push(self.lin_kernighan_step(position, [(t1, last), (t3, t4)]));
+                used.push(t3);
+                (gain, last) = (next_gain, t4);
+
+                let length = self.lin_kernighan_length(start_length, gain, t1, last);
+                if improves(length - best.0) {
+                    best = (length, steps.len());
+                }
+            }
+
+            self.undo_lin_kernighan_steps(position, &steps[best.1..]);
+            if improves(best.0 - start_length) {
+                *best_length = best.0;
+                history.push(self.path.clone());
+                return true;
+            }
+            self.undo_lin_kernighan_steps(position, &steps[..best.1]);
+        }
+
+        false
+    }
+
+    fn lin_kernighan_pass(&mut self, max_depth: usize, best_length: &mut W, history: &mut Vec<TspPath>) -> bool {
+        let n = self.path.len();
+        let mut position = vec![0; n];
+        for (k, &u) in self.path.iter().enumerate() {
+            position[u as usize] = k;
+        }
+
+        let mut improved = false;
+        for t1 in self.scan_order(0..n) {
+            let t1 = t1 as PointId;
+            for forward in [true, false] {
+                let p1 = position[t1 as usize];
+                let t2 = if forward { self.path[(p1 + 1) % n] } else { self.path[(p1 + n - 1) % n] };
+                improved |= self.try_lin_kernighan(t1, t2, max_depth, &mut position, best_length, history);
+            }
+        }
+
+        improved
+    }
+
+    // Don't-look bits: only the cities in the queue are searched for an improving 2-opt move that
+    // adds an edge to one of their partners, the candidates if set and all cities otherwise.
+    // A city without such a move leaves the queue, the four endpoints of an applied move join it
+    // again. A reversal elsewhere can change which move a settled city would make with a partner,
+    // so an empty queue is not always a local optimum: tsp() runs the queue again with every city
+    // until a run applies no move, which makes the tour 2-opt optimal over the partner lists.
+    // With complete partner lists every move is tried from all four of its endpoints,
+    // the queue pays off with short candidate lists.
+    fn dont_look_two_opt(&mut self, best_length: &mut W, history: &mut Vec<TspPath>) -> bool {
+        let n = self.path.len();
+        let candidates = self.candidates.take();
+        let mut position = vec![0; n];
+        for (k, &u) in self.path.iter().enumerate() {
+            position[u as usize] = k;
+        }
+
+        let mut queue = self.scan_order(0..n).into_iter().collect::<VecDeque<usize>>();
+        let mut active = vec![true; n];
+        let mut improved = false;
+
+        while let Some(a) = queue.pop_front() {
+            active[a] = false;
+            let partners = match &candidates {
+                Some(lists) => lists[a].iter().map(|&c| c as usize).collect(),
+                None => (0..n).filter(|&c| c != a).collect::<Vec<usize>>(),
+            };
+
+            let mut applied = None;
+            let mut best_move = None;
+            'search: for predecessors in [false, true] {
+                for &c in &partners {
+                    let (x, y) = (position[a], position[c]);
+                    let (x, y) = if predecessors { ((x + n - 1) % n, (y + n - 1) % n) } else { (x, y) };
+                    let (i, j) = (x.min(y), x.max(y));
+                    if j < i + 2 {
+                        continue;
+                    }
+
+                    if self.consider(Move::TwoOpt(i, j), best_length, history, &mut best_move) {
+                        applied = Some((i, j));
+                        break 'search;
+                    }
+                }
+            }
+
+            if let Some((_, Move::TwoOpt(i, j))) = best_move {
+                self.apply_best_move(best_move, best_length, history);
+                applied = Some((i, j));
+            }
+
+            // the reversal keeps the endpoints at the positions i, i + 1, j and j + 1
+            if let Some((i, j)) = applied {
+                improved = true;
+                for k in i + 1..=j {
+                    position[self.path[k] as usize] = k;
+                }
+                for k in [i, i + 1, j, (j + 1) % n] {
+                    let u = self.path[k] as usize;
+                    if !active[u] {
+                        active[u] = true;
+                        queue.push_back(u);
+                    }
+                }
+            }
+        }
+
+        self.candidates = candidates;
+        improved
+    }
+
+    fn two_opt_pass(&mut self, best_length: &mut W, history: &mut Vec<TspPath>) -> bool {
+        if self.dont_look_bits {
+            return self.dont_look_two_opt(best_length, history);
+        }
+        if self.candidates.is_some() {
+            return self.candidate_two_opt_pass(best_length, history);
+        }
+
+        let n = self.path.len();
+        let mut improved = false;
+        let mut best_move = None;
+        for i in self.scan_order(0..n - 1) {
+            for j in i+2..n {
+                improved |= self.consider(Move::TwoOpt(i, j), best_length, history, &mut best_move);
+            }
+        }
+
+        self.apply_best_move(best_move, best_length, history) || improved
+    }
+
+    // runs the local search until no move improves the tour and seals the incremental computation
+    pub fn tsp(&mut self) -> Result<W, ()> {
+        let length = self.improve();
         self.finish();
+        Ok(length)
+    }
+
+    // same search as tsp() but leaves the incremental computation open, so that the graph can be
+    // edited and the search run again. Call finish() once done, it seals the computation.
+    pub fn improve(&mut self) -> W {
+        let mut best_length = self.calculate_path_length();
+        let mut improved = true;
+        self.evaluations = 0;
+
+        let mut history: Vec<TspPath> = Vec::new();
+
+        while improved {
+            improved = match self.neighborhood {
+                Neighborhood::TwoOpt if self.dont_look_bits => self.dont_look_two_opt(&mut best_length, &mut history),
+                Neighborhood::TwoOpt => self.two_opt_pass(&mut best_length, &mut history),
+                Neighborhood::OrOpt => self.or_opt_pass(&mut best_length, &mut history),
+                Neighborhood::TwoOptOrOpt => {
+                    let two_opt = self.two_opt_pass(&mut best_length, &mut history);
+                    self.or_opt_pass(&mut best_length, &mut history) || two_opt
+                },
+                Neighborhood::ThreeOpt => self.three_opt_pass(&mut best_length, &mut history),
+                Neighborhood::LinKernighan { max_depth } => self.lin_kernighan_pass(max_depth, &mut best_length, &mut history),
+            };
+        }
+
         self.history = history;
-        Ok(best_length)
+        best_length
     }
 
     pub fn get_history(&self) -> &Vec<TspPath> {
         &self.history
     }
 
+    pub fn get_evaluations(&self) -> usize {
+        self.evaluations
+    }
+
     pub fn get_path(&self) -> &TspPath {
         &self.path
     }
 }
 
-impl From<Graph> for Tsp {
-    fn from(graph: Graph) -> Tsp {
+impl<W: Weight> From<Graph<W>> for Tsp<W> {
+    fn from(graph: Graph<W>) -> Tsp<W> {
         Tsp::new(Rc::new(graph), ScoreCalcTypeTSP::Fast)
     }
 }
@@ -189,6 +940,8 @@ impl From<Graph> for Tsp {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::graph::Point;
+    use crate::tsp::exact;
 
     #[test]
     fn test_tsp_slow() {
@@ -256,6 +1009,23 @@ mod tests {
         assert_eq!(length, 5);
     }
 
+    #[test]
+    fn test_seeded_starting_path() {
+        let mut graph = Graph::new();
+        graph.fill_with_random_points(20);
+        graph.fill_with_edges_full();
+        let graph = Rc::new(graph);
+
+        let mut a = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        let mut b = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        a.set_seed(42);
+        b.set_seed(42);
+
+        assert_eq!(a.generate_starting_path(), b.generate_starting_path());
+        assert_eq!(a.tsp().unwrap(), b.tsp().unwrap());
+        assert_eq!(a.get_history(), b.get_history());
+    }
+
     #[test]
     fn test_swap_edges() {
         let size = 5;
@@ -286,6 +1056,712 @@ mod tests {
         let length3 = tsp.calculate_path_length();
 
         assert_eq!(length, length3);
-    } 
+    }
+
+    fn asymmetric_instance() -> Vec<Vec<i32>> {
+        // going around 0 -> 1 -> 2 -> 3 -> 4 -> 0 is cheap, the opposite direction is expensive
+        vec![
+            vec![0, 1, 9, 8, 20],
+            vec![20, 0, 1, 9, 8],
+            vec![8, 20, 0, 1, 9],
+            vec![9, 8, 20, 0, 1],
+            vec![1, 9, 8, 20, 0]
+        ]
+    }
+
+    #[test]
+    fn test_atsp() {
+        let graph = Rc::new(Graph::from((5, asymmetric_instance())));
+        assert!(graph.is_directed());
+
+        for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Slow, ScoreCalcTypeTSP::Incremental] {
+            let mut tsp = Tsp::new(Rc::clone(&graph), score_calc_type);
+            tsp.set_starting_path(vec![0, 2, 4, 1, 3]);
+            assert_eq!(tsp.calculate_path_length_naive(), 9 + 9 + 9 + 9 + 9);
+
+            let length = tsp.tsp().unwrap();
+            assert_eq!(length, 5);
+            assert_eq!(tsp.calculate_path_length_naive(), 5);
+        }
+    }
+
+    #[test]
+    fn test_move_segment() {
+        let graph = Rc::new(Graph::from((5, asymmetric_instance())));
+        let mut tsp = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Incremental);
+        tsp.set_starting_path(vec![0, 1, 2, 3, 4]);
+
+        tsp.move_segment(0, 2, 3, false);
+        assert_eq!(tsp.get_path(), &vec![2, 3, 0, 1, 4]);
+        assert_eq!(tsp.calculate_path_length(), tsp.calculate_path_length_naive());
+        tsp.undo_move_segment(0, 2, 3, false);
+        assert_eq!(tsp.get_path(), &vec![0, 1, 2, 3, 4]);
+
+        tsp.move_segment(3, 2, 0, false);
+        assert_eq!(tsp.get_path(), &vec![0, 3, 4, 1, 2]);
+        assert_eq!(tsp.calculate_path_length(), tsp.calculate_path_length_naive());
+        tsp.undo_move_segment(3, 2, 0, false);
+        assert_eq!(tsp.get_path(), &vec![0, 1, 2, 3, 4]);
+        assert_eq!(tsp.calculate_path_length(), 5);
+    }
+
+    #[test]
+    fn test_move_reversed_segment() {
+        let mut graph: Graph<f64> = Graph::default();
+        graph.fill_with_random_points(7);
+        graph.fill_with_edges_full();
+        let mut tsp = Tsp::new(Rc::new(graph), ScoreCalcTypeTSP::Incremental);
+        tsp.set_starting_path(vec![0, 1, 2, 3, 4, 5, 6]);
+
+        tsp.move_segment(1, 3, 5, true);
+        assert_eq!(tsp.get_path(), &vec![0, 4, 5, 3, 2, 1, 6]);
+        assert!((tsp.calculate_path_length() - tsp.calculate_path_length_naive()).abs() < 1e-9);
+        tsp.undo_move_segment(1, 3, 5, true);
+        assert_eq!(tsp.get_path(), &vec![0, 1, 2, 3, 4, 5, 6]);
+
+        tsp.move_segment(4, 3, 0, true);
+        assert_eq!(tsp.get_path(), &vec![0, 6, 5, 4, 1, 2, 3]);
+        assert!((tsp.calculate_path_length() - tsp.calculate_path_length_naive()).abs() < 1e-9);
+        tsp.undo_move_segment(4, 3, 0, true);
+        assert_eq!(tsp.get_path(), &vec![0, 1, 2, 3, 4, 5, 6]);
+        assert!((tsp.calculate_path_length() - tsp.calculate_path_length_naive()).abs() < 1e-9);
+        tsp.finish();
+    }
+
+    #[test]
+    fn test_or_opt_modes_agree() {
+        let mut rng = StdRng::seed_from_u64(31);
+        let mut graph = Graph::new();
+        graph.fill_with_random_points_rng(12, &mut rng);
+        graph.fill_with_edges_full();
+        let graph = Rc::new(graph);
+        let n = graph.get_number_of_nodes();
+
+        let mut start = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        start.set_seed(5);
+        let start = start.generate_starting_path();
+
+        for neighborhood in [Neighborhood::OrOpt, Neighborhood::TwoOptOrOpt] {
+            let mut results = Vec::new();
+            for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Slow, ScoreCalcTypeTSP::Incremental] {
+                let mut tsp = Tsp::new(Rc::clone(&graph), score_calc_type);
+                tsp.set_neighborhood(neighborhood);
+                tsp.set_starting_path(start.clone());
+                let length = tsp.tsp().unwrap();
+                assert_eq!(length, tsp.calculate_path_length_naive());
+                results.push((length, tsp.get_path().clone()));
+            }
+
+            assert_eq!(results[0], results[1]);
+            assert_eq!(results[0], results[2]);
+        }
+
+        // no segment move improves the final tour, reversed or not
+        let mut tsp = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Slow);
+        tsp.set_neighborhood(Neighborhood::OrOpt);
+        tsp.set_starting_path(start);
+        let length = tsp.tsp().unwrap();
+        for len in 1..=3 {
+            for i in 0..=n - len {
+                for j in (0..n).filter(|&j| (j + n - i) % n >= len && (j + n - i) % n != n - 1) {
+                    for reversed in [false, true] {
+                        tsp.move_segment(i, len, j, reversed);
+                        assert!(tsp.calculate_path_length_naive() >= length);
+                        tsp.undo_move_segment(i, len, j, reversed);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_reconnections() {
+        let mut graph = Graph::new();
+        graph.fill_with_random_points(9);
+        graph.fill_with_edges_full();
+        let mut tsp = Tsp::new(Rc::new(graph), ScoreCalcTypeTSP::Incremental);
+        let start: TspPath = (0..9).collect();
+        tsp.set_starting_path(start.clone());
+
+        // segments 2 3 and 4 5 6 between 1 and 7
+        let expected: [TspPath; 7] = [
+            vec![0, 1, 3, 2, 4, 5, 6, 7, 8],
+            vec![0, 1, 2, 3, 6, 5, 4, 7, 8],
+            vec![0, 1, 6, 5, 4, 3, 2, 7, 8],
+            vec![0, 1, 3, 2, 6, 5, 4, 7, 8],
+            vec![0, 1, 4, 5, 6, 3, 2, 7, 8],
+            vec![0, 1, 6, 5, 4, 2, 3, 7, 8],
+            vec![0, 1, 4, 5, 6, 2, 3, 7, 8],
+        ];
+
+        for (reconnection, expected) in RECONNECTIONS.into_iter().zip(expected) {
+            tsp.reconnect(1, 3, 6, reconnection);
+            assert_eq!(tsp.get_path(), &expected);
+            assert_eq!(tsp.calculate_path_length(), tsp.calculate_path_length_naive());
+            tsp.restore_block(2, &start[2..=6]);
+            assert_eq!(tsp.get_path(), &start);
+        }
+        tsp.finish();
+    }
+
+    #[test]
+    fn test_three_opt() {
+        let mut rng = StdRng::seed_from_u64(41);
+        let mut graph: Graph<f64> = Graph::default();
+        graph.fill_with_random_points_rng(11, &mut rng);
+        graph.fill_with_edges_full();
+        let graph = Rc::new(graph);
+        let n = graph.get_number_of_nodes();
+
+        let mut start = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        start.set_seed(9);
+        let start = start.generate_starting_path();
+
+        let mut results = Vec::new();
+        for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Slow, ScoreCalcTypeTSP::Incremental] {
+            let mut tsp = Tsp::new(Rc::clone(&graph), score_calc_type);
+            tsp.set_neighborhood(Neighborhood::ThreeOpt);
+            tsp.set_starting_path(start.clone());
+            let length = tsp.tsp().unwrap();
+            assert!((length - tsp.calculate_path_length_naive()).abs() < 1e-9);
+            results.push(tsp.get_path().clone());
+        }
+        assert_eq!(results[0], results[1]);
+        assert_eq!(results[0], results[2]);
+
+        // the result is 3-opt optimal and therefore also 2-opt optimal
+        let mut tsp = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Slow);
+        tsp.set_starting_path(results[0].clone());
+        let length = tsp.calculate_path_length_naive();
+        for i in 0..n {
+            for j in i + 1..n {
+                for k in j + 1..n {
+                    for reconnection in RECONNECTIONS {
+                        let block = tsp.get_path()[i + 1..=k].to_vec();
+                        tsp.reconnect(i, j, k, reconnection);
+                        assert!(tsp.calculate_path_length_naive() > length - 1e-9);
+                        tsp.restore_block(i + 1, &block);
+                    }
+                }
+            }
+        }
+
+        let mut two_opt = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        two_opt.set_starting_path(results[0].clone());
+        assert!((two_opt.tsp().unwrap() - length).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_three_opt_atsp() {
+        let graph = Rc::new(Graph::from((5, asymmetric_instance())));
+        for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Slow, ScoreCalcTypeTSP::Incremental] {
+            let mut tsp = Tsp::new(Rc::clone(&graph), score_calc_type);
+            tsp.set_neighborhood(Neighborhood::ThreeOpt);
+            tsp.set_starting_path(vec![0, 2, 4, 1, 3]);
+            assert_eq!(tsp.tsp().unwrap(), 5);
+            assert_eq!(tsp.calculate_path_length_naive(), 5);
+        }
+    }
+
+    #[test]
+    fn test_lin_kernighan() {
+        let mut rng = StdRng::seed_from_u64(51);
+        let mut graph = Graph::new();
+        graph.fill_with_random_points_rng(20, &mut rng);
+        graph.fill_with_edges_full();
+        let graph = Rc::new(graph);
+
+        let mut start = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        start.set_seed(2);
+        let start = start.generate_starting_path();
+        let start_length = {
+            let mut tsp = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+            tsp.set_starting_path(start.clone());
+            tsp.calculate_path_length_naive()
+        };
+
+        let mut results = Vec::new();
+        for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Slow, ScoreCalcTypeTSP::Incremental] {
+            let mut tsp = Tsp::new(Rc::clone(&graph), score_calc_type);
+            tsp.set_neighborhood(Neighborhood::LinKernighan { max_depth: 6 });
+            tsp.set_starting_path(start.clone());
+            let length = tsp.tsp().unwrap();
+            assert_eq!(length, tsp.calculate_path_length_naive());
+            assert!(length < start_length);
+            results.push((length, tsp.get_path().clone(), tsp.get_history().len()));
+        }
+        assert_eq!(results[0], results[1]);
+        assert_eq!(results[0], results[2]);
+
+        // every improving 2-opt move is found as a chain of depth 1
+        let mut two_opt = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        two_opt.set_starting_path(results[0].1.clone());
+        assert_eq!(two_opt.tsp().unwrap(), results[0].0);
+        assert!(two_opt.get_history().is_empty());
+
+        let mut restricted = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        restricted.set_neighborhood(Neighborhood::LinKernighan { max_depth: 10 });
+        restricted.set_candidates(Candidates::Nearest(5));
+        restricted.set_starting_path(start);
+        let length = restricted.tsp().unwrap();
+        assert_eq!(length, restricted.calculate_path_length_naive());
+        assert!(length < start_length);
+    }
+
+    #[test]
+    #[should_panic(expected = "Directed graphs only support or-opt and 3-opt")]
+    fn test_directed_two_opt_panics() {
+        let mut tsp = Tsp::new(Rc::new(Graph::from((5, asymmetric_instance()))), ScoreCalcTypeTSP::Fast);
+        tsp.set_neighborhood(Neighborhood::TwoOpt);
+    }
+
+    #[test]
+    fn test_topology_updates() {
+        let size = 5;
+        let al = vec![
+            vec![0, 1, 7, 6, 1],
+            vec![1, 0, 1, 4, 9],
+            vec![7, 1, 0, 1, 8],
+            vec![6, 4, 1, 0, 1],
+            vec![1, 9, 8, 1, 0]
+        ];
+        let graph = Rc::new(Graph::from((size, al)));
+
+        for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Slow, ScoreCalcTypeTSP::Incremental] {
+            let mut tsp = Tsp::new(Rc::clone(&graph), score_calc_type);
+            tsp.set_starting_path(vec![4, 3, 0, 2, 1]);
+            assert_eq!(tsp.improve(), 5);
+            assert_eq!(tsp.get_path(), &vec![4, 0, 1, 2, 3]);
+
+            // the optimal tour gets more expensive, the tour 0 -> 1 -> 2 -> 3 -> 4 is kept
+            tsp.set_edge_weight(1, 2, 20);
+            assert_eq!(tsp.calculate_path_length(), 24);
+            assert_eq!(tsp.improve(), tsp.calculate_path_length_naive());
+            assert!(tsp.calculate_path_length() < 24);
+
+            // removing an edge makes it cost the missing edge penalty
+            tsp.set_starting_path(vec![0, 1, 2, 3, 4]);
+            tsp.remove_edge(3, 4);
+            assert!(tsp.calculate_path_length() > 24 + 40);
+            let penalty = tsp.penalty;
+            tsp.set_edge_weight(0, 2, 8);
+            assert_eq!(tsp.penalty, penalty, "small weights fit below the penalty");
+
+            // a weight above the bound raises the penalty of the missing edge
+            tsp.set_edge_weight(0, 2, 100);
+            assert!(tsp.penalty > 5 * 100);
+            assert_eq!(tsp.distances[3][4], tsp.penalty);
+            assert_eq!(tsp.missing, HashSet::from([(3, 4), (4, 3)]));
+            assert_eq!(tsp.calculate_path_length(), 1 + 20 + 1 + tsp.penalty + 1);
+            assert_eq!(tsp.calculate_path_length(), tsp.calculate_path_length_naive());
+            tsp.add_edge(4, 3, 2);
+            assert_eq!(tsp.calculate_path_length(), 1 + 20 + 1 + 2 + 1);
+            assert_eq!(tsp.calculate_path_length(), tsp.calculate_path_length_naive());
+            tsp.finish();
+        }
+
+        // the shared graph is left untouched
+        assert_eq!(graph.get_edge_from_lookup(1, 2).unwrap().weight, 1);
+        assert_eq!(graph.get_edge_from_lookup(3, 4).unwrap().weight, 1);
+    }
+
+    #[test]
+    fn test_sparse_graph() {
+        // ring 0 - 1 - 2 - 3 - 4 - 5 - 0 plus two chords, edges added in shuffled order
+        let mut graph = Graph::new();
+        graph.fill_with_random_points(6);
+        for (u, v, w) in [(3, 4, 1), (0, 3, 1), (5, 0, 1), (1, 2, 1), (2, 3, 1), (1, 4, 1), (0, 1, 1), (4, 5, 1)] {
+            graph.add_edge(u, v, w);
+        }
+        let graph = Rc::new(graph);
+        for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Slow, ScoreCalcTypeTSP::Incremental] {
+            let mut tsp = Tsp::new(Rc::clone(&graph), score_calc_type);
+            assert_eq!(tsp.penalty, 2 * graph.get_missing_edge_penalty());
+            assert!(tsp.penalty <= graph.get_max_missing_edge_penalty());
+            tsp.set_starting_path(vec![0, 2, 4, 1, 3, 5]);
+            assert_eq!(tsp.calculate_path_length(), 2 + 4 * tsp.penalty);
+
+            let length = tsp.tsp().unwrap();
+            assert_eq!(length, 6, "the ring is the only tour without missing edges");
+            assert_eq!(tsp.calculate_path_length_naive(), 6);
+        }
+    }
+
+    #[test]
+    fn test_sparse_graph_without_overflow() {
+        // a ring of heavy i32 edges, n * (2 * (n * 20000 + 1)) does not fit in i32
+        let n = 300;
+        let mut graph = Graph::new();
+        graph.fill_with_circular_layout(n);
+        for u in 0..n {
+            graph.add_edge(u, (u + 1) % n, 20000);
+        }
+        let graph = Rc::new(graph);
+        let mut path = (0..n).collect::<TspPath>();
+        path.shuffle(&mut StdRng::seed_from_u64(7));
+
+        for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Slow, ScoreCalcTypeTSP::Incremental] {
+            let mut tsp = Tsp::new(Rc::clone(&graph), score_calc_type);
+            assert_eq!(tsp.penalty, graph.get_max_missing_edge_penalty());
+            tsp.set_starting_path(path.clone());
+            let length = tsp.calculate_path_length();
+            assert!(length > 0);
+            assert_eq!(length, tsp.calculate_path_length_naive());
+
+            // the even nodes, then the odd ones, only the edge 299 - 0 exists
+            tsp.set_starting_path((0..n).map(|u| 2 * u % n + (2 * u / n)).collect());
+            assert_eq!(tsp.calculate_path_length(), 20000 + (n - 1) * tsp.penalty);
+
+            // raising a weight keeps the capped penalty
+            tsp.set_edge_weight(0, 1, 30000);
+            assert_eq!(tsp.penalty, graph.get_max_missing_edge_penalty());
+            tsp.finish();
+        }
+    }
+
+    #[test]
+    fn test_float_weights() {
+        // unit square with its centre, the optimal tour has length 4 (edges) - 1 + 2 * sqrt(0.5)
+        let mut graph: Graph<f64> = Graph::default();
+        graph.add_nodes(vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(0.0, 1.0),
+            Point::new(0.5, 0.5),
+        ]);
+        graph.fill_with_edges_full();
+        let graph = Rc::new(graph);
+        let expected = 3.0 + 2.0 * 0.5_f64.sqrt();
+
+        for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Slow, ScoreCalcTypeTSP::Incremental] {
+            let mut tsp = Tsp::new(Rc::clone(&graph), score_calc_type);
+            tsp.set_starting_path(vec![0, 2, 4, 1, 3]);
+
+            let length = tsp.tsp().unwrap();
+            assert!((length - expected).abs() < 1e-9, "length {} should be {}", length, expected);
+            assert!((tsp.calculate_path_length_naive() - expected).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_candidate_two_opt() {
+        let mut rng = StdRng::seed_from_u64(21);
+        let mut graph: Graph<f64> = Graph::default();
+        graph.fill_with_random_points_rng(60, &mut rng);
+        graph.fill_with_edges_full();
+        let graph = Rc::new(graph);
+        let n = graph.get_number_of_nodes();
+
+        let mut start = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        start.set_seed(3);
+        let start = start.generate_starting_path();
+
+        // the complete candidate lists allow every 2-opt move, so the result is 2-opt optimal
+        let mut tsp = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        tsp.set_candidates(Candidates::Nearest(n - 1));
+        tsp.set_starting_path(start.clone());
+        let length = tsp.tsp().unwrap();
+        assert!((length - tsp.calculate_path_length_naive()).abs() < 1e-6);
+        for i in 0..n - 1 {
+            for j in i + 2..n {
+                let path = tsp.get_path();
+                let delta = tsp.weight(path[i], path[j]) + tsp.weight(path[i + 1], path[(j + 1) % n])
+                    - tsp.weight(path[i], path[i + 1]) - tsp.weight(path[j], path[(j + 1) % n]);
+                assert!(!improves(delta), "2-opt move ({}, {}) still improves", i, j);
+            }
+        }
+
+        let mut paths = Vec::new();
+        for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Slow, ScoreCalcTypeTSP::Incremental] {
+            let mut tsp = Tsp::new(Rc::clone(&graph), score_calc_type);
+            tsp.set_candidates(Candidates::Quadrant(2));
+            tsp.set_starting_path(start.clone());
+            let length = tsp.tsp().unwrap();
+
+            let mut visited = tsp.get_path().clone();
+            visited.sort();
+            assert_eq!(visited, (0..n as PointId).collect::<Vec<PointId>>());
+            assert!((length - tsp.calculate_path_length_naive()).abs() < 1e-6);
+            paths.push(tsp.get_path().clone());
+        }
+
+        assert_eq!(paths[0], paths[1]);
+        assert_eq!(paths[0], paths[2]);
+    }
+
+    #[test]
+    fn test_i64_weights() {
+        let al: Vec<Vec<i64>> = vec![
+            vec![0, 1, 7, 6, 1],
+            vec![1, 0, 1, 4, 9],
+            vec![7, 1, 0, 1, 8],
+            vec![6, 4, 1, 0, 1],
+            vec![1, 9, 8, 1, 0]
+        ].into_iter().map(|row| row.into_iter().map(|w| w * 3_000_000_000).collect()).collect();
+
+        let graph = Rc::new(Graph::from((5, al)));
+        let mut tsp = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Incremental);
+        tsp.set_starting_path(vec![4, 3, 0, 2, 1]);
+
+        assert_eq!(tsp.tsp().unwrap(), 5 * 3_000_000_000);
+    }
+
+    #[test]
+    fn test_construct_starting_path() {
+        let mut rng = StdRng::seed_from_u64(71);
+        let mut graph: Graph<f64> = Graph::default();
+        graph.fill_with_random_points_rng(15, &mut rng);
+        graph.fill_with_edges_full();
+        let graph = Rc::new(graph);
+
+        for construction in [Construction::Random, Construction::NearestNeighbor, Construction::GreedyEdge, Construction::CheapestInsertion,
+                Construction::FarthestInsertion, Construction::SpaceFillingCurve, Construction::MstDoubleTree] {
+            let mut tsp = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Incremental);
+            tsp.set_seed(5);
+            let path = tsp.construct_starting_path(construction);
+            assert_eq!(&path, tsp.get_path());
+
+            // the incremental length follows the constructed path and the search only improves it
+            let start = tsp.calculate_path_length_naive();
+            assert!((tsp.calculate_path_length() - start).abs() < 1e-6, "{:?}", construction);
+            assert!(tsp.tsp().unwrap() <= start + 1e-9);
+        }
+    }
+
+    // runs every score mode from the same start and checks them against the exact optimum
+    fn assert_modes_against_exact<W: Weight>(graph: Rc<Graph<W>>, neighborhoods: &[Neighborhood], seed: u64) {
+        let (_, optimal) = exact::held_karp(&graph);
+        let mut start = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        start.set_seed(seed);
+        let start = start.generate_starting_path();
+
+        for &neighborhood in neighborhoods {
+            let mut results = Vec::new();
+            for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Slow, ScoreCalcTypeTSP::Incremental] {
+                let mut tsp = Tsp::new(Rc::clone(&graph), score_calc_type);
+                tsp.set_neighborhood(neighborhood);
+                tsp.set_starting_path(start.clone());
+                let length = tsp.tsp().unwrap();
+
+                assert!((length - tsp.calculate_path_length_naive()).to_f64().abs() < 1e-6);
+                assert!(length.to_f64() >= optimal.to_f64() - 1e-6, "{:?} found {:?} below the optimum {:?}", neighborhood, length, optimal);
+                results.push((tsp.get_path().clone(), length));
+            }
+
+            assert_eq!(results[0].0, results[1].0, "{:?}", neighborhood);
+            assert_eq!(results[0].0, results[2].0, "{:?}", neighborhood);
+        }
+    }
+
+    #[test]
+    fn test_local_optima_against_exact() {
+        let mut rng = StdRng::seed_from_u64(101);
+        for seed in 0..4 {
+            let mut graph: Graph<f64> = Graph::default();
+            graph.fill_with_random_points_rng(8, &mut rng);
+            graph.fill_with_edges_full();
+            let neighborhoods = [Neighborhood::TwoOpt, Neighborhood::OrOpt, Neighborhood::TwoOptOrOpt,
+                Neighborhood::ThreeOpt, Neighborhood::LinKernighan { max_depth: 4 }];
+            assert_modes_against_exact(Rc::new(graph), &neighborhoods, seed);
+
+            // asymmetric integer weights
+            let al = (0..7).map(|u| (0..7).map(|v| if u == v { 0 } else { rng.gen_range(1..50) }).collect()).collect();
+            let graph: Graph<i32> = Graph::from_adjacency_matrix_rng(7, al, &mut rng);
+            assert_modes_against_exact(Rc::new(graph), &[Neighborhood::OrOpt, Neighborhood::ThreeOpt], seed);
+        }
+
+        // 3-opt reaches the optimum of the small instance used throughout these tests
+        let graph = Rc::new(Graph::from((5, asymmetric_instance())));
+        let mut tsp = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        tsp.set_neighborhood(Neighborhood::ThreeOpt);
+        tsp.set_starting_path(vec![0, 2, 4, 1, 3]);
+        assert_eq!(tsp.tsp().unwrap(), exact::branch_and_bound(&graph).1);
+    }
+
+    #[test]
+    fn test_pivoting_rules() {
+        let mut rng = StdRng::seed_from_u64(111);
+        let mut graph: Graph<f64> = Graph::default();
+        graph.fill_with_random_points_rng(9, &mut rng);
+        graph.fill_with_edges_full();
+        let graph = Rc::new(graph);
+        let n = graph.get_number_of_nodes();
+        let (_, optimal) = exact::held_karp(&graph);
+
+        let mut start = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        start.set_seed(4);
+        let start = start.generate_starting_path();
+
+        let neighborhoods = [Neighborhood::TwoOpt, Neighborhood::OrOpt, Neighborhood::ThreeOpt, Neighborhood::LinKernighan { max_depth: 3 }];
+        for neighborhood in neighborhoods {
+            for pivoting_rule in [PivotingRule::FirstImprovement, PivotingRule::BestImprovement, PivotingRule::RandomOrderFirst] {
+                if matches!(neighborhood, Neighborhood::LinKernighan { .. }) && pivoting_rule == PivotingRule::BestImprovement {
+                    continue;
+                }
+
+                let mut results = Vec::new();
+                for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Slow, ScoreCalcTypeTSP::Incremental] {
+                    let mut tsp = Tsp::new(Rc::clone(&graph), score_calc_type);
+                    tsp.set_seed(9);
+                    tsp.set_neighborhood(neighborhood);
+                    tsp.set_pivoting_rule(pivoting_rule);
+                    tsp.set_starting_path(start.clone());
+                    let length = tsp.tsp().unwrap();
+
+                    assert!((length - tsp.calculate_path_length_naive()).abs() < 1e-6);
+                    assert!(length >= optimal - 1e-6);
+                    results.push((tsp.get_path().clone(), tsp.get_history().clone()));
+                }
+
+                assert_eq!(results[0], results[1], "{:?} {:?}", neighborhood, pivoting_rule);
+                assert_eq!(results[0], results[2], "{:?} {:?}", neighborhood, pivoting_rule);
+            }
+        }
+
+        // best improvement starts with the 2-opt move that shortens the tour the most
+        let mut tsp = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        tsp.set_starting_path(start.clone());
+        let start_length = tsp.calculate_path_length_naive();
+        let best_delta = (0..n - 1)
+            .flat_map(|i| (i + 2..n).map(move |j| (i, j)))
+            .map(|(i, j)| tsp.fast_delta(Move::TwoOpt(i, j)))
+            .fold(f64::INFINITY, f64::min);
+
+        tsp.set_pivoting_rule(PivotingRule::BestImprovement);
+        tsp.tsp().unwrap();
+        tsp.set_starting_path(tsp.get_history()[0].clone());
+        assert!((tsp.calculate_path_length_naive() - (start_length + best_delta)).abs() < 1e-9);
+
+        // candidate lists and the combined neighbourhood follow the rule as well
+        for pivoting_rule in [PivotingRule::BestImprovement, PivotingRule::RandomOrderFirst] {
+            let mut lengths = Vec::new();
+            for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Incremental] {
+                let mut tsp = Tsp::new(Rc::clone(&graph), score_calc_type);
+                tsp.set_seed(2);
+                tsp.set_candidates(Candidates::Nearest(4));
+                tsp.set_neighborhood(Neighborhood::TwoOptOrOpt);
+                tsp.set_pivoting_rule(pivoting_rule);
+                tsp.set_starting_path(start.clone());
+                lengths.push(tsp.tsp().unwrap());
+            }
+            assert!((lengths[0] - lengths[1]).abs() < 1e-9);
+        }
+    }
+
+    // no 2-opt move that adds an edge from a city to one of its candidates improves the tour
+    fn assert_two_opt_optimal(tsp: &Tsp<f64>, candidates: &[Vec<PointId>]) {
+        let n = tsp.path.len();
+        let mut position = vec![0; n];
+        for (k, &u) in tsp.path.iter().enumerate() {
+            position[u as usize] = k;
+        }
+
+        for (a, partners) in candidates.iter().enumerate() {
+            for &c in partners {
+                for predecessors in [false, true] {
+                    let (x, y) = (position[a], position[c as usize]);
+                    let (x, y) = if predecessors { ((x + n - 1) % n, (y + n - 1) % n) } else { (x, y) };
+                    let (i, j) = (x.min(y), x.max(y));
+                    if j >= i + 2 {
+                        assert!(!improves(tsp.fast_delta(Move::TwoOpt(i, j))), "move {} {} improves", i, j);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_dont_look_bits() {
+        let mut rng = StdRng::seed_from_u64(121);
+        let mut graph: Graph<f64> = Graph::default();
+        graph.fill_with_random_points_rng(80, &mut rng);
+        graph.fill_with_edges_full();
+        let graph = Rc::new(graph);
+
+        let mut start = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        start.set_seed(6);
+        let start = start.generate_starting_path();
+
+        // the queue pays off with candidate lists, it skips the cities whose surroundings have settled
+        for start in [start.clone(), construction::greedy_edge(&graph)] {
+            let mut results = Vec::new();
+            for dont_look_bits in [false, true] {
+                let mut tsp = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+                tsp.set_candidates(Candidates::Nearest(8));
+                tsp.set_dont_look_bits(dont_look_bits);
+                tsp.set_starting_path(start.clone());
+                let length = tsp.tsp().unwrap();
+
+                assert!((length - tsp.calculate_path_length_naive()).abs() < 1e-6);
+                assert_two_opt_optimal(&tsp, &graph.get_candidate_lists(Candidates::Nearest(8)));
+                results.push((length, tsp.get_evaluations()));
+            }
+            assert!(results[1].1 < results[0].1, "{:?}", results);
+            assert!(results[1].0 < 1.05 * results[0].0, "{:?}", results);
+        }
+
+        // a single run of the queue leaves an improving move on this instance
+        let mut rng = StdRng::seed_from_u64(9);
+        let mut short: Graph<f64> = Graph::default();
+        short.fill_with_random_points_rng(40, &mut rng);
+        short.fill_with_edges_full();
+        let mut tsp = Tsp::new(Rc::new(short), ScoreCalcTypeTSP::Fast);
+        tsp.set_seed(9);
+        tsp.set_candidates(Candidates::Nearest(3));
+        tsp.set_dont_look_bits(true);
+        tsp.generate_starting_path();
+        tsp.tsp().unwrap();
+        assert_two_opt_optimal(&tsp, &tsp.graph.get_candidate_lists(Candidates::Nearest(3)));
+
+        // every score mode takes the same moves with the same number of evaluations,
+        // so Incremental saves as many TspComp evaluations as Fast saves delta computations
+        let mut small: Graph<f64> = Graph::default();
+        small.add_nodes(graph.get_nodes()[..20].to_vec());
+        small.fill_with_edges_full();
+        let small = Rc::new(small);
+        let small_start = (0..20).rev().collect::<TspPath>();
+        for pivoting_rule in [PivotingRule::FirstImprovement, PivotingRule::BestImprovement, PivotingRule::RandomOrderFirst] {
+            let mut results = Vec::new();
+            for score_calc_type in [ScoreCalcTypeTSP::Fast, ScoreCalcTypeTSP::Slow, ScoreCalcTypeTSP::Incremental] {
+                let mut tsp = Tsp::new(Rc::clone(&small), score_calc_type);
+                tsp.set_seed(8);
+                tsp.set_candidates(Candidates::Nearest(5));
+                tsp.set_dont_look_bits(true);
+                tsp.set_pivoting_rule(pivoting_rule);
+                tsp.set_starting_path(small_start.clone());
+                let length = tsp.tsp().unwrap();
+
+                assert!((length - tsp.calculate_path_length_naive()).abs() < 1e-6);
+                results.push((tsp.get_path().clone(), tsp.get_history().clone(), tsp.get_evaluations()));
+            }
+
+            assert_eq!(results[0], results[1], "{:?}", pivoting_rule);
+            assert_eq!(results[0], results[2], "{:?}", pivoting_rule);
+        }
+
+        // with candidate lists and or-opt the queue works on the candidate edges only
+        let mut tsp = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        tsp.set_candidates(Candidates::Quadrant(2));
+        tsp.set_dont_look_bits(true);
+        tsp.set_neighborhood(Neighborhood::TwoOptOrOpt);
+        tsp.set_starting_path(start.clone());
+        let length = tsp.tsp().unwrap();
+        let mut visited = tsp.get_path().clone();
+        visited.sort();
+        assert_eq!(visited, (0..80).collect::<TspPath>());
+        assert!((length - tsp.calculate_path_length_naive()).abs() < 1e-6);
+    }
+
+    #[test]
+    #[should_panic(expected = "Lin-Kernighan does not support best improvement")]
+    fn test_lin_kernighan_best_improvement_panics() {
+        let mut graph: Graph<f64> = Graph::default();
+        graph.fill_with_random_points(5);
+        graph.fill_with_edges_full();
+        let mut tsp = Tsp::from(graph);
+        tsp.set_pivoting_rule(PivotingRule::BestImprovement);
+        tsp.set_neighborhood(Neighborhood::LinKernighan { max_depth: 3 });
+    }
 }
 
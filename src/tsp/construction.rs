@@ -0,0 +1,424 @@
+use crate::graph::{Graph, PointId, Weight};
+use crate::tsp::TspPath;
+
+// Construction heuristics for starting tours. Pairs without an edge cost the missing edge
+// penalty of the graph, as in the local search. Greedy edge, the insertions and the double tree
+// look at both directions of a pair as the same edge and are meant for symmetric instances.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Construction {
+    Random,
+    NearestNeighbor,
+    GreedyEdge,
+    CheapestInsertion,
+    FarthestInsertion,
+    SpaceFillingCurve,
+    MstDoubleTree,
+}
+
+fn distances<W: Weight>(graph: &Graph<W>) -> Vec<Vec<W>> {
+    graph.get_penalized_distance_matrix()
+}
+
+// follows the successor array from node 0
+fn tour_from_successors(next: &[usize]) -> TspPath {
+    let mut path = Vec::with_capacity(next.len());
+    let mut u = 0;
+    for _ in 0..next.len() {
+        path.push(u as PointId);
+        u = next[u];
+    }
+    path
+}
+
+pub fn nearest_neighbor<W: Weight>(graph: &Graph<W>, start: PointId) -> TspPath {
+    let d = distances(graph);
+    let n = d.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mut visited = vec![false; n];
+    let mut path = vec![start];
+    visited[start as usize] = true;
+
+    let mut u = start as usize;
+    for _ in 1..n {
+        let v = (0..n)
+            .filter(|&v| !visited[v])
+            .min_by(|&a, &b| d[u][a].partial_cmp(&d[u][b]).unwrap())
+            .unwrap();
+        visited[v] = true;
+        path.push(v as PointId);
+        u = v;
+    }
+
+    path
+}
+
+fn find(parent: &mut [usize], u: usize) -> usize {
+    let mut root = u;
+    while parent[root] != root {
+        root = parent[root];
+    }
+
+    let mut u = u;
+    while parent[u] != root {
+        let next = parent[u];
+        parent[u] = root;
+        u = next;
+    }
+    root
+}
+
+// takes the shortest edges first as long as no node gets a third edge and no cycle is closed,
+// the two ends of the resulting path are joined at the end
+pub fn greedy_edge<W: Weight>(graph: &Graph<W>) -> TspPath {
+    let d = distances(graph);
+    let n = d.len();
+    if n < 3 {
+        return (0..n as PointId).collect();
+    }
+
+    let mut pairs = (0..n).flat_map(|u| (u + 1..n).map(move |v| (u, v))).collect::<Vec<(usize, usize)>>();
+    pairs.sort_by(|&(a, b), &(c, e)| d[a][b].partial_cmp(&d[c][e]).unwrap());
+
+    let mut parent = (0..n).collect::<Vec<usize>>();
+    let mut adjacent: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut taken = 0;
+
+    for (u, v) in pairs {
+        if taken == n - 1 {
+            break;
+        }
+        if adjacent[u].len() == 2 || adjacent[v].len() == 2 {
+            continue;
+        }
+
+        let (ru, rv) = (find(&mut parent, u), find(&mut parent, v));
+        if ru == rv {
+            continue;
+        }
+
+        parent[ru] = rv;
+        adjacent[u].push(v);
+        adjacent[v].push(u);
+        taken += 1;
+    }
+
+    // walk the hamiltonian path from one of its ends
+    let mut path = Vec::with_capacity(n);
+    let (mut prev, mut u) = (usize::MAX, (0..n).find(|&u| adjacent[u].len() < 2).unwrap());
+    for _ in 0..n {
+        path.push(u as PointId);
+        let next = adjacent[u].iter().copied().find(|&v| v != prev);
+        prev = u;
+        match next {
+            Some(v) => u = v,
+            None => break,
+        }
+    }
+
+    path
+}
+
+// cost of inserting x between a and b
+fn insertion_cost<W: Weight>(d: &[Vec<W>], a: usize, x: usize, b: usize) -> W {
+    d[a][x] + d[x][b] - d[a][b]
+}
+
+// cheapest position for x in the tour given by the successor array, as the node to insert after
+fn cheapest_position<W: Weight>(d: &[Vec<W>], next: &[usize], in_tour: &[bool], x: usize) -> (W, usize) {
+    (0..next.len())
+        .filter(|&a| in_tour[a])
+        .map(|a| (insertion_cost(d, a, x, next[a]), a))
+        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then(a.1.cmp(&b.1)))
+        .unwrap()
+}
+
+// start tour of the insertion heuristics: node 0 and the node chosen by `second`
+fn initial_pair(n: usize, second: usize) -> (Vec<usize>, Vec<bool>) {
+    let mut next = vec![usize::MAX; n];
+    let mut in_tour = vec![false; n];
+    next[0] = second;
+    next[second] = 0;
+    in_tour[0] = true;
+    in_tour[second] = true;
+    (next, in_tour)
+}
+
+// repeatedly inserts the node that increases the tour length the least at its cheapest position
+pub fn cheapest_insertion<W: Weight>(graph: &Graph<W>) -> TspPath {
+    let d = distances(graph);
+    let n = d.len();
+    if n < 3 {
+        return (0..n as PointId).collect();
+    }
+
+    let closest = (1..n).min_by(|&a, &b| d[0][a].partial_cmp(&d[0][b]).unwrap()).unwrap();
+    let (mut next, mut in_tour) = initial_pair(n, closest);
+
+    // best position of every node outside of the tour, only recomputed when its edge is replaced
+    let mut best: Vec<(W, usize)> = (0..n).map(|x| cheapest_position(&d, &next, &in_tour, x)).collect();
+
+    for _ in 2..n {
+        let x = (0..n)
+            .filter(|&x| !in_tour[x])
+            .min_by(|&a, &b| best[a].0.partial_cmp(&best[b].0).unwrap().then(a.cmp(&b)))
+            .unwrap();
+        let a = best[x].1;
+        let b = next[a];
+        next[a] = x;
+        next[x] = b;
+        in_tour[x] = true;
+
+        for y in (0..n).filter(|&y| !in_tour[y]) {
+            if best[y].1 == a {
+                best[y] = cheapest_position(&d, &next, &in_tour, y);
+                continue;
+            }
+
+            for (cost, after) in [(insertion_cost(&d, a, y, x), a), (insertion_cost(&d, x, y, b), x)] {
+                if cost < best[y].0 {
+                    best[y] = (cost, after);
+                }
+            }
+        }
+    }
+
+    tour_from_successors(&next)
+}
+
+// repeatedly inserts the node farthest away from the tour at its cheapest position,
+// which outlines the instance early and usually beats the cheapest insertion
+pub fn farthest_insertion<W: Weight>(graph: &Graph<W>) -> TspPath {
+    let d = distances(graph);
+    let n = d.len();
+    if n < 3 {
+        return (0..n as PointId).collect();
+    }
+
+    let farthest = (1..n).max_by(|&a, &b| d[0][a].partial_cmp(&d[0][b]).unwrap()).unwrap();
+    let (mut next, mut in_tour) = initial_pair(n, farthest);
+
+    // distance of every node to the closest node of the tour
+    let mut distance_to_tour = (0..n)
+        .map(|x| if d[0][x] < d[farthest][x] { d[0][x] } else { d[farthest][x] })
+        .collect::<Vec<W>>();
+
+    for _ in 2..n {
+        let x = (0..n)
+            .filter(|&x| !in_tour[x])
+            .max_by(|&a, &b| distance_to_tour[a].partial_cmp(&distance_to_tour[b]).unwrap().then(b.cmp(&a)))
+            .unwrap();
+        let (_, a) = cheapest_position(&d, &next, &in_tour, x);
+        let b = next[a];
+        next[a] = x;
+        next[x] = b;
+        in_tour[x] = true;
+
+        for y in 0..n {
+            if d[x][y] < distance_to_tour[y] {
+                distance_to_tour[y] = d[x][y];
+            }
+        }
+    }
+
+    tour_from_successors(&next)
+}
+
+// position of (x, y) on the Hilbert curve filling a 2^order x 2^order grid
+fn hilbert_index(order: u32, mut x: u64, mut y: u64) -> u64 {
+    let mut index = 0;
+    let mut s = 1 << (order - 1);
+    while s > 0 {
+        let rx = ((x & s) > 0) as u64;
+        let ry = ((y & s) > 0) as u64;
+        index += s * s * ((3 * rx) ^ ry);
+
+        // rotate the quadrant so that the curve stays continuous
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - (x & (s - 1)) + (x & !(s - 1));
+                y = s - 1 - (y & (s - 1)) + (y & !(s - 1));
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s >>= 1;
+    }
+    index
+}
+
+// visits the nodes in the order of a Hilbert curve over the bounding box,
+// O(n log n) and only looks at the coordinates
+pub fn space_filling_curve<W: Weight>(graph: &Graph<W>) -> TspPath {
+    const ORDER: u32 = 16;
+    let cells = ((1u64 << ORDER) - 1) as f64;
+    let (top, bottom, left, right) = graph.get_bounding_box();
+    let scale = |value: f64, low: f64, high: f64| {
+        if high > low { ((value - low) / (high - low) * cells).round() as u64 } else { 0 }
+    };
+
+    let mut keyed = graph
+        .get_nodes()
+        .iter()
+        .enumerate()
+        .map(|(u, node)| (hilbert_index(ORDER, scale(node.x, left, right), scale(node.y, top, bottom)), u as PointId))
+        .collect::<Vec<(u64, PointId)>>();
+    keyed.sort_unstable();
+    keyed.into_iter().map(|(_, u)| u).collect()
+}
+
+// Preorder walk of a minimum spanning tree, which is the doubled tree with repeated nodes skipped.
+// For metric instances the tour is at most twice as long as the optimal one.
+pub fn mst_double_tree<W: Weight>(graph: &Graph<W>) -> TspPath {
+    let d = distances(graph);
+    let n = d.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    // Prim on the dense matrix
+    let mut in_tree = vec![false; n];
+    let mut parent = vec![0; n];
+    let mut cost = d[0].clone();
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); n];
+    in_tree[0] = true;
+
+    for _ in 1..n {
+        let v = (0..n)
+            .filter(|&v| !in_tree[v])
+            .min_by(|&a, &b| cost[a].partial_cmp(&cost[b]).unwrap())
+            .unwrap();
+        in_tree[v] = true;
+        children[parent[v]].push(v);
+
+        for w in (0..n).filter(|&w| !in_tree[w]) {
+            if d[v][w] < cost[w] {
+                cost[w] = d[v][w];
+                parent[w] = v;
+            }
+        }
+    }
+
+    let mut path = Vec::with_capacity(n);
+    let mut stack = vec![0];
+    while let Some(u) = stack.pop() {
+        path.push(u as PointId);
+        stack.extend(children[u].iter().rev());
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Point;
+    use crate::tsp::exact;
+    use nannou::rand::rngs::StdRng;
+    use nannou::rand::SeedableRng;
+
+    fn length<W: Weight>(graph: &Graph<W>, path: &TspPath) -> W {
+        let d = distances(graph);
+        (0..path.len()).map(|i| d[path[i] as usize][path[(i + 1) % path.len()] as usize]).sum()
+    }
+
+    fn assert_permutation(path: &TspPath, n: usize) {
+        let mut sorted = path.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..n as PointId).collect::<Vec<PointId>>());
+    }
+
+    fn all(graph: &Graph<f64>) -> Vec<(&'static str, TspPath)> {
+        vec![
+            ("nearest neighbor", nearest_neighbor(graph, 0)),
+            ("greedy edge", greedy_edge(graph)),
+            ("cheapest insertion", cheapest_insertion(graph)),
+            ("farthest insertion", farthest_insertion(graph)),
+            ("space filling curve", space_filling_curve(graph)),
+            ("mst double tree", mst_double_tree(graph)),
+        ]
+    }
+
+    #[test]
+    fn test_points_on_a_line() {
+        // every heuristic finds the optimal tour of collinear points, twice the width
+        let mut graph: Graph<f64> = Graph::default();
+        graph.add_nodes([3.0, 0.0, 7.0, 1.0, 4.5, 9.0].iter().map(|&x| Point::new(x, 2.0)).collect());
+        graph.fill_with_edges_full();
+
+        for (name, path) in all(&graph) {
+            assert_permutation(&path, 6);
+            assert!((length(&graph, &path) - 18.0).abs() < 1e-9, "{}", name);
+        }
+        assert_eq!(nearest_neighbor(&graph, 0), vec![0, 4, 2, 5, 3, 1]);
+    }
+
+    #[test]
+    fn test_approximation() {
+        let mut rng = StdRng::seed_from_u64(61);
+        for _ in 0..5 {
+            let mut graph: Graph<f64> = Graph::default();
+            graph.fill_with_random_points_rng(8, &mut rng);
+            graph.fill_with_edges_full();
+            let (_, optimal) = exact::held_karp(&graph);
+
+            for (name, path) in all(&graph) {
+                assert_permutation(&path, 8);
+                assert!(length(&graph, &path) >= optimal - 1e-9, "{}", name);
+            }
+            assert!(length(&graph, &mst_double_tree(&graph)) <= 2.0 * optimal + 1e-9);
+            // every insertion heuristic is within ceil(log2 n) + 1 of the optimum (Rosenkrantz, Stearns and Lewis)
+            assert!(length(&graph, &farthest_insertion(&graph)) <= 4.0 * optimal + 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_better_than_random() {
+        let mut rng = StdRng::seed_from_u64(62);
+        let mut graph: Graph<f64> = Graph::default();
+        graph.fill_with_random_points_rng(200, &mut rng);
+        graph.fill_with_edges_full();
+        let random = length(&graph, &(0..200).collect());
+
+        for (name, path) in all(&graph) {
+            assert_permutation(&path, 200);
+            assert!(length(&graph, &path) < random / 4.0, "{}", name);
+        }
+
+        // the missing edge penalty steers the constructions onto the edges of a sparse graph
+        let mut ring = Graph::new();
+        ring.add_nodes((0..6).map(|i| Point::new(i as f64, (i * i) as f64)).collect());
+        for (u, v) in [(0, 3), (3, 1), (1, 5), (5, 2), (2, 4), (4, 0)] {
+            ring.add_edge(u, v, 1);
+        }
+        for path in [nearest_neighbor(&ring, 0), greedy_edge(&ring), cheapest_insertion(&ring), farthest_insertion(&ring)] {
+            assert_eq!(length(&ring, &path), 6);
+        }
+    }
+
+    #[test]
+    fn test_tiny_graphs() {
+        for n in 0..3 {
+            let mut graph: Graph<f64> = Graph::default();
+            graph.fill_with_random_points(n);
+            for (_, path) in all(&graph) {
+                assert_permutation(&path, n as usize);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hilbert_index() {
+        // the order 1 curve visits (0, 0), (0, 1), (1, 1), (1, 0)
+        assert_eq!([(0, 0), (0, 1), (1, 1), (1, 0)].map(|(x, y)| hilbert_index(1, x, y)), [0, 1, 2, 3]);
+
+        // consecutive cells of the curve are neighbours in the grid
+        let mut cells = (0..16u64).flat_map(|x| (0..16u64).map(move |y| (hilbert_index(4, x, y), x, y))).collect::<Vec<_>>();
+        cells.sort_unstable();
+        for pair in cells.windows(2) {
+            let (dx, dy) = (pair[0].1.abs_diff(pair[1].1), pair[0].2.abs_diff(pair[1].2));
+            assert_eq!(dx + dy, 1);
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use crate::graph::{Graph, PointId, Weight};
+use crate::tsp::{construction, TspPath};
+
+// Exact solvers for small instances, used as an oracle for the local search.
+// Both respect the direction of the edges and charge the missing edge penalty like Tsp does.
+// Held-Karp needs O(2^n n) memory and O(2^n n^2) time, branch and bound has no memory limit
+// but may take exponential time on unlucky instances.
+
+pub const MAX_HELD_KARP_NODES: usize = 18;
+
+fn distances<W: Weight>(graph: &Graph<W>) -> Vec<Vec<W>> {
+    graph.get_penalized_distance_matrix()
+}
+
+fn tour_length<W: Weight>(d: &[Vec<W>], path: &[PointId]) -> W {
+    let n = path.len();
+    (0..n).map(|i| d[path[i] as usize][path[(i + 1) % n] as usize]).sum()
+}
+
+// small instances have a single tour up to rotation
+fn trivial<W: Weight>(d: &[Vec<W>]) -> Option<(TspPath, W)> {
+    if d.len() > 2 {
+        return None;
+    }
+
+    let path = (0..d.len() as PointId).collect::<TspPath>();
+    let length = tour_length(d, &path);
+    Some((path, length))
+}
+
+// Dynamic programming over subsets: best[set][v] is the length of the shortest path that starts
+// at node 0, visits the nodes of `set` (subsets of 1..n, bit v - 1 stands for node v) and ends at v
+pub fn held_karp<W: Weight>(graph: &Graph<W>) -> (TspPath, W) {
+    let d = distances(graph);
+    if let Some(solution) = trivial(&d) {
+        return solution;
+    }
+
+    let n = d.len();
+    assert!(n <= MAX_HELD_KARP_NODES, "Held-Karp supports at most {} nodes, found {}", MAX_HELD_KARP_NODES, n);
+
+    let m = n - 1;
+    let full = (1usize << m) - 1;
+    let mut best: Vec<Vec<Option<W>>> = vec![vec![None; n]; full + 1];
+    let mut parent = vec![vec![0u8; n]; full + 1];
+    for v in 1..n {
+        best[1 << (v - 1)][v] = Some(d[0][v]);
+    }
+
+    for set in 1..=full {
+        for v in (1..n).filter(|&v| set & (1 << (v - 1)) != 0) {
+            let Some(length) = best[set][v] else { continue };
+            for w in (1..n).filter(|&w| set & (1 << (w - 1)) == 0) {
+                let next = set | (1 << (w - 1));
+                let candidate = length + d[v][w];
+                if best[next][w].is_none_or(|current| candidate < current) {
+                    best[next][w] = Some(candidate);
+                    parent[next][w] = v as u8;
+                }
+            }
+        }
+    }
+
+    let (length, last) = (1..n)
+        .map(|v| (best[full][v].unwrap() + d[v][0], v))
+        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+        .unwrap();
+
+    // walk the parents back from the last node
+    let mut path = Vec::with_capacity(n);
+    let (mut set, mut v) = (full, last);
+    while v != 0 {
+        path.push(v as PointId);
+        let previous = parent[set][v] as usize;
+        set &= !(1 << (v - 1));
+        v = previous;
+    }
+    path.push(0);
+    path.reverse();
+
+    (path, length)
+}
+
+struct Search<'a, W: Weight> {
+    d: &'a [Vec<W>],
+    // cheapest edge leaving every node, the remaining nodes need at least these edges
+    cheapest_out: Vec<W>,
+    // other nodes sorted by distance, tried closest first
+    order: Vec<Vec<usize>>,
+    path: Vec<usize>,
+    visited: Vec<bool>,
+    best: (TspPath, W),
+}
+
+impl<'a, W: Weight> Search<'a, W> {
+    fn search(&mut self, length: W, remaining_out: W) {
+        let n = self.d.len();
+        let u = *self.path.last().unwrap();
+        if self.path.len() == n {
+            let total = length + self.d[u][0];
+            if total < self.best.1 {
+                self.best = (self.path.iter().map(|&v| v as PointId).collect(), total);
+            }
+            return;
+        }
+
+        // every unvisited node and the current one still need an outgoing edge
+        if length + remaining_out >= self.best.1 {
+            return;
+        }
+
+        for i in 0..self.order[u].len() {
+            let v = self.order[u][i];
+            if self.visited[v] {
+                continue;
+            }
+
+            self.visited[v] = true;
+            self.path.push(v);
+            self.search(length + self.d[u][v], remaining_out - self.cheapest_out[u]);
+            self.path.pop();
+            self.visited[v] = false;
+        }
+    }
+}
+
+// Depth first search over the tours starting at node 0, the starting upper bound comes from
+// a farthest insertion tour and a branch is cut when the path plus the cheapest outgoing edge
+// of every node that still has to be left is not shorter than the best tour found
+pub fn branch_and_bound<W: Weight>(graph: &Graph<W>) -> (TspPath, W) {
+    let d = distances(graph);
+    if let Some(solution) = trivial(&d) {
+        return solution;
+    }
+
+    let n = d.len();
+    let cheapest_out = (0..n)
+        .map(|u| (0..n).filter(|&v| v != u).map(|v| d[u][v]).min_by(|a, b| a.partial_cmp(b).unwrap()).unwrap())
+        .collect::<Vec<W>>();
+    let order = (0..n)
+        .map(|u| {
+            let mut others = (0..n).filter(|&v| v != u).collect::<Vec<usize>>();
+            others.sort_by(|&a, &b| d[u][a].partial_cmp(&d[u][b]).unwrap());
+            others
+        })
+        .collect();
+
+    let start = construction::farthest_insertion(graph);
+    let start_length = tour_length(&d, &start);
+    let mut search = Search {
+        d: &d,
+        cheapest_out: cheapest_out.clone(),
+        order,
+        path: vec![0],
+        visited: vec![false; n],
+        best: (start, start_length),
+    };
+    search.visited[0] = true;
+    search.search(W::default(), cheapest_out.into_iter().sum());
+
+    // the starting tour may begin anywhere, the tours of the search begin at node 0
+    let (mut path, length) = search.best;
+    let zero = path.iter().position(|&u| u == 0).unwrap();
+    path.rotate_left(zero);
+    (path, length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Point;
+    use crate::tsp::lower_bound;
+    use nannou::rand::rngs::StdRng;
+    use nannou::rand::{Rng, SeedableRng};
+
+    fn brute_force<W: Weight>(graph: &Graph<W>) -> W {
+        fn permute<W: Weight>(d: &[Vec<W>], path: &mut Vec<PointId>, k: usize, best: &mut Option<W>) {
+            if k == path.len() {
+                let length = tour_length(d, path);
+                if best.is_none_or(|current| length < current) {
+                    *best = Some(length);
+                }
+                return;
+            }
+            for i in k..path.len() {
+                path.swap(k, i);
+                permute(d, path, k + 1, best);
+                path.swap(k, i);
+            }
+        }
+
+        let d = distances(graph);
+        let mut path = (0..d.len() as PointId).collect::<Vec<PointId>>();
+        let mut best = None;
+        permute(&d, &mut path, 1, &mut best);
+        best.unwrap()
+    }
+
+    fn assert_solution<W: Weight>(graph: &Graph<W>, solution: &(TspPath, W)) {
+        let mut sorted = solution.0.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..graph.get_number_of_nodes() as PointId).collect::<Vec<PointId>>());
+        assert_eq!(solution.0[0], 0);
+        assert_eq!(tour_length(&distances(graph), &solution.0), solution.1);
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = StdRng::seed_from_u64(91);
+        for n in 3..=8 {
+            let mut graph: Graph<f64> = Graph::default();
+            graph.fill_with_random_points_rng(n, &mut rng);
+            graph.fill_with_edges_full();
+
+            let dp = held_karp(&graph);
+            let bb = branch_and_bound(&graph);
+            assert_solution(&graph, &dp);
+            assert_solution(&graph, &bb);
+            assert!((dp.1 - brute_force(&graph)).abs() < 1e-9);
+            assert!((bb.1 - dp.1).abs() < 1e-9);
+            assert!(lower_bound::held_karp_bound(&graph) <= dp.1 + 1e-6);
+        }
+
+        // asymmetric integer weights
+        for n in 3..=7 {
+            let al = (0..n).map(|u| (0..n).map(|v| if u == v { 0 } else { rng.gen_range(1..100) }).collect()).collect();
+            let graph: Graph<i32> = Graph::from_adjacency_matrix_rng(n, al, &mut rng);
+            assert!(graph.is_directed());
+
+            let dp = held_karp(&graph);
+            assert_solution(&graph, &dp);
+            assert_eq!(dp.1, brute_force(&graph));
+            assert_eq!(branch_and_bound(&graph).1, dp.1);
+        }
+    }
+
+    #[test]
+    fn test_larger_instances() {
+        let mut rng = StdRng::seed_from_u64(92);
+        let mut graph: Graph<f64> = Graph::default();
+        graph.fill_with_random_points_rng(13, &mut rng);
+        graph.fill_with_edges_full();
+
+        let dp = held_karp(&graph);
+        let bb = branch_and_bound(&graph);
+        assert_solution(&graph, &bb);
+        assert!((bb.1 - dp.1).abs() < 1e-9);
+
+        // the optimal tour of points on a circle is the polygon
+        let mut circle: Graph<f64> = Graph::default();
+        circle.fill_with_circular_layout(14);
+        circle.fill_with_edges_full();
+        let (path, _) = held_karp(&circle);
+        for i in 0..14 {
+            assert!([1, 13].contains(&((path[(i + 1) % 14] - path[i] + 14) % 14)));
+        }
+    }
+
+    #[test]
+    fn test_tiny_graphs() {
+        let empty: Graph<f64> = Graph::default();
+        assert_eq!(held_karp(&empty), (vec![], 0.0));
+        assert_eq!(branch_and_bound(&empty), (vec![], 0.0));
+
+        let mut pair: Graph<f64> = Graph::default();
+        pair.add_nodes(vec![Point::new(0.0, 0.0), Point::new(3.0, 4.0)]);
+        pair.fill_with_edges_full();
+        assert_eq!(held_karp(&pair), (vec![0, 1], 10.0));
+        assert_eq!(branch_and_bound(&pair), (vec![0, 1], 10.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Held-Karp supports at most")]
+    fn test_held_karp_too_large() {
+        let mut graph: Graph<f64> = Graph::default();
+        graph.fill_with_random_points(MAX_HELD_KARP_NODES as i32 + 1);
+        held_karp(&graph);
+    }
+}
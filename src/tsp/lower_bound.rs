@@ -0,0 +1,221 @@
+use crate::graph::{Graph, PointId, Weight, EPS};
+use crate::tsp::construction;
+
+// Held-Karp lower bound. A tour is a 1-tree (a spanning tree of the nodes 1..n plus two edges
+// at node 0) in which every node has degree 2, so the minimum 1-tree is never longer than the
+// optimal tour. Charging every edge (u, v) an extra pi[u] + pi[v] adds exactly 2 * sum(pi) to
+// every tour but not to every 1-tree, the subgradient optimisation searches the pi that
+// maximises the bound by raising pi at nodes of degree > 2 and lowering it at leaves.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OneTree {
+    // weight of the 1-tree under pi minus 2 * sum(pi)
+    pub bound: f64,
+    pub edges: Vec<(PointId, PointId)>,
+    pub degrees: Vec<usize>,
+}
+
+const MAX_ITERATIONS: usize = 1000;
+// the step size is halved after this many iterations without a better bound
+const PATIENCE: usize = 30;
+
+// edge weights as f64, directed graphs use the cheaper direction which keeps the bound valid
+fn symmetric_distances<W: Weight>(graph: &Graph<W>) -> Vec<Vec<f64>> {
+    let d = graph.get_penalized_distance_matrix();
+    let n = d.len();
+    (0..n).map(|u| (0..n).map(|v| d[u][v].to_f64().min(d[v][u].to_f64())).collect()).collect()
+}
+
+pub fn minimum_one_tree(d: &[Vec<f64>], pi: &[f64]) -> OneTree {
+    let n = d.len();
+    let weight = |u: usize, v: usize| d[u][v] + pi[u] + pi[v];
+    let mut tree = OneTree { bound: -2.0 * pi.iter().sum::<f64>(), edges: Vec::new(), degrees: vec![0; n] };
+    let add = |tree: &mut OneTree, u: usize, v: usize| {
+        tree.bound += weight(u, v);
+        tree.edges.push((u as PointId, v as PointId));
+        tree.degrees[u] += 1;
+        tree.degrees[v] += 1;
+    };
+
+    if n < 3 {
+        // the only tour goes back and forth
+        if n == 2 {
+            add(&mut tree, 0, 1);
+            add(&mut tree, 1, 0);
+        }
+        return tree;
+    }
+
+    // Prim on the nodes 1..n
+    let mut in_tree = vec![false; n];
+    let mut parent = vec![1; n];
+    let mut cost = (0..n).map(|v| weight(1, v)).collect::<Vec<f64>>();
+    in_tree[0] = true;
+    in_tree[1] = true;
+
+    for _ in 2..n {
+        let v = (0..n).filter(|&v| !in_tree[v]).min_by(|&a, &b| cost[a].total_cmp(&cost[b])).unwrap();
+        in_tree[v] = true;
+        add(&mut tree, parent[v], v);
+
+        for w in (0..n).filter(|&w| !in_tree[w]) {
+            if weight(v, w) < cost[w] {
+                cost[w] = weight(v, w);
+                parent[w] = v;
+            }
+        }
+    }
+
+    // the two cheapest edges of node 0
+    let mut closest = (1..n).collect::<Vec<usize>>();
+    closest.sort_by(|&a, &b| weight(0, a).total_cmp(&weight(0, b)));
+    add(&mut tree, 0, closest[0]);
+    add(&mut tree, 0, closest[1]);
+
+    tree
+}
+
+// the plain minimum 1-tree bound, without any penalties
+pub fn one_tree_bound<W: Weight>(graph: &Graph<W>) -> f64 {
+    let n = graph.get_number_of_nodes();
+    minimum_one_tree(&symmetric_distances(graph), &vec![0.0; n]).bound
+}
+
+pub fn held_karp_bound<W: Weight>(graph: &Graph<W>) -> f64 {
+    held_karp_bound_iterations(graph, MAX_ITERATIONS)
+}
+
+// Subgradient optimisation with the step size of Held, Wolfe and Crowder:
+// t = lambda * (upper - bound) / |degrees - 2|^2, where the upper bound is a farthest insertion tour
+pub fn held_karp_bound_iterations<W: Weight>(graph: &Graph<W>, iterations: usize) -> f64 {
+    let d = symmetric_distances(graph);
+    let n = d.len();
+    let mut pi = vec![0.0; n];
+    let mut best = minimum_one_tree(&d, &pi).bound;
+    if n < 3 {
+        return best;
+    }
+
+    let tour = construction::farthest_insertion(graph);
+    let upper: f64 = (0..n).map(|i| d[tour[i] as usize][tour[(i + 1) % n] as usize]).sum();
+
+    let mut lambda = 2.0;
+    let mut since_improvement = 0;
+    for _ in 0..iterations {
+        let tree = minimum_one_tree(&d, &pi);
+        if tree.bound > best + EPS {
+            best = tree.bound;
+            since_improvement = 0;
+        } else {
+            since_improvement += 1;
+            if since_improvement == PATIENCE {
+                lambda /= 2.0;
+                since_improvement = 0;
+            }
+        }
+
+        let norm: f64 = tree.degrees.iter().map(|&degree| (degree as f64 - 2.0).powi(2)).sum();
+        // every node has degree 2, the 1-tree is an optimal tour
+        if norm == 0.0 || lambda < 1e-6 || best >= upper - EPS {
+            break;
+        }
+
+        let step = lambda * (upper - tree.bound) / norm;
+        for (u, &degree) in tree.degrees.iter().enumerate() {
+            pi[u] += step * (degree as f64 - 2.0);
+        }
+    }
+
+    best
+}
+
+// relative distance of a tour length to a lower bound, 0 means the tour is optimal
+pub fn optimality_gap<W: Weight>(length: W, bound: f64) -> f64 {
+    (length.to_f64() - bound) / bound
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Point;
+    use crate::tsp::{Neighborhood, ScoreCalcTypeTSP, Tsp};
+    use nannou::rand::rngs::StdRng;
+    use nannou::rand::SeedableRng;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_one_tree() {
+        // a square with one diagonal cheaper than the sides
+        let d = vec![
+            vec![0.0, 2.0, 1.0, 2.0],
+            vec![2.0, 0.0, 2.0, 3.0],
+            vec![1.0, 2.0, 0.0, 2.0],
+            vec![2.0, 3.0, 2.0, 0.0],
+        ];
+        let tree = minimum_one_tree(&d, &[0.0; 4]);
+        assert_eq!(tree.bound, 7.0);
+        assert_eq!(tree.edges.len(), 4);
+        assert_eq!(tree.degrees.iter().sum::<usize>(), 8);
+        assert_eq!(tree.degrees[0], 2);
+
+        // penalties are taken out of the bound again
+        let pi = [0.5, -1.0, 2.0, 0.25];
+        let penalized = minimum_one_tree(&d, &pi);
+        let plain: f64 = penalized.edges.iter().map(|&(u, v)| d[u as usize][v as usize]).sum();
+        assert!((penalized.bound - plain - penalized.degrees.iter().zip(pi).map(|(&k, p)| (k as f64 - 2.0) * p).sum::<f64>()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_held_karp_bound() {
+        // on a regular polygon the minimum 1-tree already is the optimal tour, the perimeter
+        let mut circle: Graph<f64> = Graph::default();
+        circle.fill_with_circular_layout(12);
+        circle.fill_with_edges_full();
+        let nodes = circle.get_nodes();
+        let perimeter: f64 = (0..12).map(|i| nodes[i].distance(&nodes[(i + 1) % 12], circle.get_metric())).sum();
+        assert!((one_tree_bound(&circle) - perimeter).abs() < 1e-6 * perimeter);
+        assert!((held_karp_bound(&circle) - perimeter).abs() < 1e-6 * perimeter);
+
+        let mut rng = StdRng::seed_from_u64(81);
+        let mut graph: Graph<f64> = Graph::default();
+        graph.fill_with_random_points_rng(40, &mut rng);
+        graph.fill_with_edges_full();
+        let graph = Rc::new(graph);
+
+        let mut tsp = Tsp::new(Rc::clone(&graph), ScoreCalcTypeTSP::Fast);
+        tsp.set_seed(1);
+        tsp.set_neighborhood(Neighborhood::ThreeOpt);
+        tsp.generate_starting_path();
+        let length = tsp.tsp().unwrap();
+
+        let bound = held_karp_bound(&graph);
+        // the penalties close most of the gap left by the plain 1-tree
+        assert!(one_tree_bound(&graph) < bound - 1.0);
+        assert!(bound <= length);
+        // the Held-Karp bound is usually within 1% of the optimum on random euclidean instances
+        // and 3-opt within a few percent
+        assert!(optimality_gap(length, bound) < 0.05, "gap {}", optimality_gap(length, bound));
+    }
+
+    #[test]
+    fn test_small_and_directed() {
+        let mut single: Graph<f64> = Graph::default();
+        single.add_nodes(vec![Point::new(1.0, 1.0)]);
+        assert_eq!(held_karp_bound(&single), 0.0);
+
+        let mut pair: Graph<f64> = Graph::default();
+        pair.add_nodes(vec![Point::new(0.0, 0.0), Point::new(3.0, 4.0)]);
+        pair.fill_with_edges_full();
+        assert_eq!(held_karp_bound(&pair), 10.0);
+
+        // a directed cycle 0 -> 1 -> 2 -> 3 -> 0 of weight 1 with expensive reverse edges
+        let mut directed = Graph::new_directed();
+        directed.add_nodes((0..4).map(|i| Point::new(i as f64, 0.0)).collect());
+        for u in 0..4 {
+            directed.add_edge(u, (u + 1) % 4, 1);
+            directed.add_edge((u + 1) % 4, u, 10);
+        }
+        assert!((held_karp_bound(&directed) - 4.0).abs() < 1e-6);
+        assert_eq!(optimality_gap(5, 4.0), 0.25);
+    }
+}
@@ -1,15 +1,62 @@
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
 use adapton::macros::*;
 use adapton::engine::*;
 use adapton::reflect;
 
-pub struct TspComp {
+use crate::graph::Weight;
+
+// adapton requires Eq + Hash of every value stored in the DCG,
+// weights are compared by their bit pattern so that floats can be used as well
+#[derive(Debug, Clone, Copy)]
+struct DcgWeight<W: Weight>(W);
+
+impl<W: Weight> PartialEq for DcgWeight<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl<W: Weight> Eq for DcgWeight<W> {}
+
+impl<W: Weight> Hash for DcgWeight<W> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+// row of the distance matrix, shared behind an Rc so that reading it in a thunk does not copy it
+#[derive(Debug, Clone)]
+struct DcgRow<W: Weight>(Rc<Vec<W>>);
+
+impl<W: Weight> PartialEq for DcgRow<W> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.len() == other.0.len() && self.0.iter().zip(other.0.iter()).all(|(a, b)| a.to_bits() == b.to_bits())
+    }
+}
+
+impl<W: Weight> Eq for DcgRow<W> {}
+
+impl<W: Weight> Hash for DcgRow<W> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for weight in self.0.iter() {
+            weight.to_bits().hash(state);
+        }
+    }
+}
+
+pub struct TspComp<W: Weight = i32> {
     input_nodes: Vec<Art<i32>>,
-    res: Art<i32>,
+    // the distances are inputs as well, an edge node only depends on the row of its first vertex
+    distance_rows: Vec<Art<DcgRow<W>>>,
+    res: Art<DcgWeight<W>>,
     sealed: bool
 }
 
-impl TspComp {
-    pub fn new(al: &'static Vec<Vec<i32>>, n: usize) -> TspComp {
+impl<W: Weight> TspComp<W> {
+    pub fn new(al: Rc<Vec<Vec<W>>>, n: usize) -> TspComp<W> {
         manage::init_dcg();
 
         if cfg!(feature = "traces") {
@@ -21,10 +68,15 @@ impl TspComp {
             cell!(0)
         }).collect();
 
-        let res = TspComp::create_computation_graph(&input_nodes, al);
+        let distance_rows = al.iter().map(|row| {
+            cell!(DcgRow(Rc::new(row.clone())))
+        }).collect::<Vec<Art<DcgRow<W>>>>();
+
+        let res = TspComp::create_computation_graph(&input_nodes, &distance_rows);
         
         TspComp {
             input_nodes,
+            distance_rows,
             res,
             sealed: false
         }
@@ -38,8 +90,23 @@ impl TspComp {
         }
     }
 
-    pub fn get_result(&self) -> i32 {
-        get!(self.res)
+    // (u, v, weight) sets the distance from u to v
+    pub fn update_distances(&mut self, updates: Vec<(usize, usize, W)>) {
+        self.ensure_unsealed();
+        // every changed row is copied once and set once
+        let mut rows: HashMap<usize, Vec<W>> = HashMap::new();
+        for (u, v, weight) in updates {
+            let row = rows.entry(u).or_insert_with(|| get!(self.distance_rows[u]).0.to_vec());
+            row[v] = weight;
+        }
+
+        for (u, row) in rows {
+            set(&self.distance_rows[u], DcgRow(Rc::new(row)));
+        }
+    }
+
+    pub fn get_result(&self) -> W {
+        get!(self.res).0
     }
 
     pub fn seal(&mut self) {
@@ -58,22 +125,24 @@ impl TspComp {
         assert!(!self.sealed, "TspComp is sealed");
     }
 
-    fn create_computation_graph(input_nodes: &Vec<Art<i32>>, al: &'static Vec<Vec<i32>>) -> Art<i32> {
+    fn create_computation_graph(input_nodes: &Vec<Art<i32>>, distance_rows: &[Art<DcgRow<W>>]) -> Art<DcgWeight<W>> {
         // first layer contains the input nodes, which are the indices of the nodes in the adjacency list
-        // second layer retrieves edges from adjacency list
+        // second layer retrieves edges from the distance rows
+        let rows = Rc::new(distance_rows.to_vec());
         let mut outputs = input_nodes.windows(2).map(|chunk| {
             let a = chunk[0].clone();
             let b = chunk[1].clone();
-            thunk!(al[get!(a) as usize][get!(b) as usize])
-        }).collect::<Vec<Art<i32>>>();
+            let rows = Rc::clone(&rows);
+            thunk!(DcgWeight(get!(rows[get!(a) as usize]).0[get!(b) as usize]))
+        }).collect::<Vec<Art<DcgWeight<W>>>>();
 
         // last and first vertex
         let last = input_nodes[input_nodes.len() - 1].clone();
         let first: Art<i32> = input_nodes[0].clone();
-        let closing_connection = thunk!(al[get!(last) as usize][get!(first) as usize]);
+        let closing_connection = thunk!(DcgWeight(get!(rows[get!(last) as usize]).0[get!(first) as usize]));
         outputs.push(closing_connection);
 
-        fn devide_and_conquer(nodes: &Vec<Art<i32>>, left: usize, right: usize) -> Art<i32> {
+        fn devide_and_conquer<W: Weight>(nodes: &Vec<Art<DcgWeight<W>>>, left: usize, right: usize) -> Art<DcgWeight<W>> {
             if left == right {
                 return nodes[left].clone();
             }
@@ -82,7 +151,7 @@ impl TspComp {
             let left_res = devide_and_conquer(nodes, left, mid);
             let right_res = devide_and_conquer(nodes, mid + 1, right);
     
-            thunk!(get!(left_res) + get!(right_res))
+            thunk!(DcgWeight(get!(left_res).0 + get!(right_res).0))
         }
         
         // subsequent layers sum up the edges
@@ -97,16 +166,15 @@ mod tests {
     #[test]
     fn test() {
         let size = 5;
-        let al = Box::new(vec![
+        let al = Rc::new(vec![
             vec![0, 1, 7, 6, 1],
             vec![1, 0, 1, 4, 9],
             vec![7, 1, 0, 1, 8],
             vec![6, 4, 1, 0, 1],
             vec![1, 9, 8, 1, 0]
         ]);
-        let static_al: &'static mut Vec<Vec<i32>> = Box::leak(al);
 
-        let mut tsp_comp = TspComp::new(static_al, size);
+        let mut tsp_comp = TspComp::new(al, size);
         assert_eq!(tsp_comp.get_result(), 0);
 
         let updates = vec![
@@ -131,4 +199,48 @@ mod tests {
         assert_eq!(tsp_comp.get_result(), 5);
         tsp_comp.seal();
     }
+
+    #[test]
+    fn test_update_distances() {
+        let al = Rc::new(vec![
+            vec![0, 1, 7, 6, 1],
+            vec![1, 0, 1, 4, 9],
+            vec![7, 1, 0, 1, 8],
+            vec![6, 4, 1, 0, 1],
+            vec![1, 9, 8, 1, 0]
+        ]);
+
+        let mut tsp_comp = TspComp::new(al, 5);
+        tsp_comp.update_input_nodes(vec![(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)]);
+        assert_eq!(tsp_comp.get_result(), 5);
+
+        tsp_comp.update_distances(vec![(1, 2, 10), (2, 1, 10)]);
+        assert_eq!(tsp_comp.get_result(), 14);
+
+        // only one direction, the tour uses 3 -> 4
+        tsp_comp.update_distances(vec![(4, 3, 5), (3, 4, 2), (0, 2, 3)]);
+        assert_eq!(tsp_comp.get_result(), 15);
+
+        tsp_comp.update_input_nodes(vec![(1, 2), (2, 1)]);
+        // 0 -> 2 -> 1 -> 3 -> 4 -> 0
+        assert_eq!(tsp_comp.get_result(), 3 + 10 + 4 + 2 + 1);
+        tsp_comp.seal();
+    }
+
+    #[test]
+    fn test_float_weights() {
+        let al = Rc::new(vec![
+            vec![0.0, 1.5, 2.25],
+            vec![1.5, 0.0, 0.5],
+            vec![2.25, 0.5, 0.0]
+        ]);
+
+        let mut tsp_comp = TspComp::new(al, 3);
+        tsp_comp.update_input_nodes(vec![(0, 0), (1, 1), (2, 2)]);
+        assert_eq!(tsp_comp.get_result(), 4.25);
+
+        tsp_comp.update_input_nodes(vec![(0, 2), (2, 0)]);
+        assert_eq!(tsp_comp.get_result(), 4.25);
+        tsp_comp.seal();
+    }
 }
\ No newline at end of file
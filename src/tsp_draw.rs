@@ -1,32 +1,35 @@
 use std::collections::VecDeque;
+use std::env;
 use std::rc::Rc;
 use std::time::Duration;
 
 use nannou::prelude::*;
 
 use crate::graph::{Graph, Point};
-use crate::tsp::{Tsp, TspPath, ScoreCalcTypeTSP};
+use crate::tsp::{lower_bound, Tsp, TspPath, ScoreCalcTypeTSP};
+use crate::serialization::{self, Format, TspRun};
+use crate::viewport::Viewport;
 
 struct Model {
     graph: Rc<Graph>,
     path: TspPath,
-    history: VecDeque<TspPath>
+    history: VecDeque<TspPath>,
+    // Held-Karp lower bound of the instance, the gap of the drawn tour is measured against it
+    bound: f64
 }
 
 fn draw_vertices(draw: &Draw, boundary: &Rect, model: &Model) {
     let nodes: &Vec<Point> = model.graph.get_nodes();
-    let graph_boundary = model.graph.get_boundary();
+    let viewport = Viewport::new(model.graph.get_bounding_box(), boundary, 20.0);
 
     for node in nodes {
-        let x = map_range(node.x, graph_boundary.2, graph_boundary.3, boundary.left(), boundary.right());
-        let y = map_range(node.y, graph_boundary.0, graph_boundary.1, boundary.bottom(), boundary.top());
-        draw.ellipse().x_y(x, y).radius(5.0).color(WHITE);
+        draw.ellipse().xy(viewport.map(node)).radius(5.0).color(WHITE);
     }
 }
 
 fn draw_path(draw: &Draw, boundary: &Rect, path: TspPath, model: &Model) {
     let nodes = model.graph.get_nodes();
-    let graph_boundary = model.graph.get_boundary();
+    let viewport = Viewport::new(model.graph.get_bounding_box(), boundary, 20.0);
 
     let n = path.len();
     for i in 0..n {
@@ -35,16 +38,11 @@ fn draw_path(draw: &Draw, boundary: &Rect, path: TspPath, model: &Model) {
         let u = &nodes[u as usize];
         let v = &nodes[v as usize];
 
-        let ux = map_range(u.x, graph_boundary.2, graph_boundary.3, boundary.left(), boundary.right());
-        let uy = map_range(u.y, graph_boundary.0, graph_boundary.1, boundary.bottom(), boundary.top());
-        let vx = map_range(v.x, graph_boundary.2, graph_boundary.3, boundary.left(), boundary.right());
-        let vy = map_range(v.y, graph_boundary.0, graph_boundary.1, boundary.bottom(), boundary.top());
-
-        draw.line().start(pt2(ux, uy)).end(pt2(vx, vy)).color(ROYALBLUE);
+        draw.line().start(viewport.map(u)).end(viewport.map(v)).color(ROYALBLUE);
     }
 }
 
-fn model(_app: &App) -> Model {
+fn solve() -> TspRun {
     let mut tsp_graph = Graph::new();
     tsp_graph.fill_with_random_points(200);
     tsp_graph.fill_with_edges_full();
@@ -52,15 +50,50 @@ fn model(_app: &App) -> Model {
     let tsp_graph = Rc::new(tsp_graph);
     let mut tsp = Tsp::new(Rc::clone(&tsp_graph), ScoreCalcTypeTSP::Fast);
     let path = tsp.generate_starting_path();
-    let length = tsp.tsp().unwrap();
+    tsp.tsp().unwrap();
+
+    TspRun {
+        graph: (*tsp_graph).clone(),
+        starting_path: path,
+        history: tsp.get_history().clone()
+    }
+}
+
+// TSP_REPLAY=<file> replays a saved run instead of solving a new instance,
+// TSP_SAVE=<file> archives the run, files ending with .json are saved as JSON
+fn model(_app: &App) -> Model {
+    let run = match env::var("TSP_REPLAY") {
+        Ok(file) => serialization::load(&file, Format::from_path(&file)).unwrap(),
+        Err(_) => solve()
+    };
+
+    if let Ok(file) = env::var("TSP_SAVE") {
+        serialization::save(&run, &file, Format::from_path(&file)).unwrap();
+    }
 
     Model {
-        graph: tsp_graph,
-        path: path.clone(),
-        history: VecDeque::from(tsp.get_history().clone())
+        bound: lower_bound::held_karp_bound(&run.graph),
+        graph: Rc::new(run.graph),
+        path: run.starting_path,
+        history: VecDeque::from(run.history)
     }
 }
 
+fn draw_statistics(draw: &Draw, boundary: &Rect, model: &Model) {
+    let n = model.path.len();
+    let length: i32 = (0..n)
+        .filter_map(|i| model.graph.get_edge_from_lookup(model.path[i], model.path[(i + 1) % n]))
+        .map(|edge| edge.weight)
+        .sum();
+    let gap = 100.0 * lower_bound::optimality_gap(length, model.bound);
+
+    draw.text(&format!("length: {}  lower bound: {:.1}  gap: {:.2}%", length, model.bound, gap))
+        .x_y(boundary.left() + 200.0, boundary.top() - 15.0)
+        .w(380.0)
+        .left_justify()
+        .color(WHITE);
+}
+
 fn update(_app: &App, model: &mut Model, _update: Update) {
     let path = model.history.pop_front();
 
@@ -80,6 +113,7 @@ fn view(app: &App, model: &Model, frame: Frame) {
 
     draw_vertices(&draw, &boundary, model);
     draw_path(&draw, &boundary, model.path.clone(), model);
+    draw_statistics(&draw, &boundary, model);
 
     draw.to_frame(app, &frame).unwrap();
 }
@@ -0,0 +1,553 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::graph::{Graph, Metric, Point};
+
+// Loader for TSPLIB .tsp instances
+// http://comopt.ifi.uni-heidelberg.de/software/TSPLIB95/tsp95.pdf
+
+// the largest TSPLIB instance, pla85900, fits; the loader builds O(n^2) edges or weights
+pub const MAX_DIMENSION: usize = 100_000;
+
+#[derive(Debug)]
+pub enum TsplibError {
+    Io(io::Error),
+    MissingDimension,
+    InvalidDimension { line: usize, value: String },
+    MissingEdgeWeightType,
+    MissingEdgeWeightFormat,
+    MissingSection(&'static str),
+    UnsupportedProblemType(String),
+    UnsupportedEdgeWeightType(String),
+    UnsupportedEdgeWeightFormat(String),
+    InvalidLine { line: usize, content: String },
+    InvalidNumber { line: usize, value: String },
+    InvalidNodeId { line: usize, id: i64 },
+    DuplicateNode { line: usize, id: i64 },
+    WrongNumberOfEntries { section: &'static str, expected: usize, found: usize },
+}
+
+impl fmt::Display for TsplibError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TsplibError::Io(err) => write!(f, "io error: {}", err),
+            TsplibError::MissingDimension => write!(f, "DIMENSION is missing"),
+            TsplibError::InvalidDimension { line, value } => {
+                write!(f, "line {}: DIMENSION must be a whole number between 1 and {}, found '{}'", line, MAX_DIMENSION, value)
+            }
+            TsplibError::MissingEdgeWeightType => write!(f, "EDGE_WEIGHT_TYPE is missing"),
+            TsplibError::MissingEdgeWeightFormat => write!(f, "EDGE_WEIGHT_FORMAT is required for EXPLICIT weights"),
+            TsplibError::MissingSection(section) => write!(f, "{} is missing", section),
+            TsplibError::UnsupportedProblemType(t) => write!(f, "unsupported TYPE: {}", t),
+            TsplibError::UnsupportedEdgeWeightType(t) => write!(f, "unsupported EDGE_WEIGHT_TYPE: {}", t),
+            TsplibError::UnsupportedEdgeWeightFormat(t) => write!(f, "unsupported EDGE_WEIGHT_FORMAT: {}", t),
+            TsplibError::InvalidLine { line, content } => write!(f, "line {}: unexpected '{}'", line, content),
+            TsplibError::InvalidNumber { line, value } => write!(f, "line {}: '{}' is not a number", line, value),
+            TsplibError::InvalidNodeId { line, id } => write!(f, "line {}: node id {} is out of range", line, id),
+            TsplibError::DuplicateNode { line, id } => write!(f, "line {}: node {} is defined twice", line, id),
+            TsplibError::WrongNumberOfEntries { section, expected, found } => {
+                write!(f, "{}: expected {} entries, found {}", section, expected, found)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TsplibError {}
+
+impl From<io::Error> for TsplibError {
+    fn from(err: io::Error) -> Self {
+        TsplibError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeWeightType {
+    Euc2d,
+    Ceil2d,
+    Att,
+    Geo,
+    Explicit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EdgeWeightFormat {
+    FullMatrix,
+    UpperRow,
+    LowerRow,
+    UpperDiagRow,
+    LowerDiagRow,
+    UpperCol,
+    LowerCol,
+    UpperDiagCol,
+    LowerDiagCol,
+}
+
+impl EdgeWeightType {
+    fn parse(value: &str) -> Result<EdgeWeightType, TsplibError> {
+        match value {
+            "EUC_2D" => Ok(EdgeWeightType::Euc2d),
+            "CEIL_2D" => Ok(EdgeWeightType::Ceil2d),
+            "ATT" => Ok(EdgeWeightType::Att),
+            "GEO" => Ok(EdgeWeightType::Geo),
+            "EXPLICIT" => Ok(EdgeWeightType::Explicit),
+            _ => Err(TsplibError::UnsupportedEdgeWeightType(value.to_string())),
+        }
+    }
+
+    // metric following the rounding rules of TSPLIB95, explicit weights have none
+    pub fn metric(&self) -> Option<Metric> {
+        match self {
+            EdgeWeightType::Euc2d => Some(Metric::Euc2d),
+            EdgeWeightType::Ceil2d => Some(Metric::Ceil2d),
+            EdgeWeightType::Att => Some(Metric::Att),
+            EdgeWeightType::Geo => Some(Metric::Geo),
+            EdgeWeightType::Explicit => None,
+        }
+    }
+}
+
+impl EdgeWeightFormat {
+    fn parse(value: &str) -> Result<EdgeWeightFormat, TsplibError> {
+        match value {
+            "FULL_MATRIX" => Ok(EdgeWeightFormat::FullMatrix),
+            "UPPER_ROW" => Ok(EdgeWeightFormat::UpperRow),
+            "LOWER_ROW" => Ok(EdgeWeightFormat::LowerRow),
+            "UPPER_DIAG_ROW" => Ok(EdgeWeightFormat::UpperDiagRow),
+            "LOWER_DIAG_ROW" => Ok(EdgeWeightFormat::LowerDiagRow),
+            "UPPER_COL" => Ok(EdgeWeightFormat::UpperCol),
+            "LOWER_COL" => Ok(EdgeWeightFormat::LowerCol),
+            "UPPER_DIAG_COL" => Ok(EdgeWeightFormat::UpperDiagCol),
+            "LOWER_DIAG_COL" => Ok(EdgeWeightFormat::LowerDiagCol),
+            _ => Err(TsplibError::UnsupportedEdgeWeightFormat(value.to_string())),
+        }
+    }
+
+    fn number_of_entries(&self, n: usize) -> usize {
+        match self {
+            EdgeWeightFormat::FullMatrix => n * n,
+            EdgeWeightFormat::UpperRow | EdgeWeightFormat::LowerCol
+            | EdgeWeightFormat::LowerRow | EdgeWeightFormat::UpperCol => n * (n - 1) / 2,
+            _ => n * (n + 1) / 2,
+        }
+    }
+
+    // (row, column) pairs in the order in which the entries are listed in the file,
+    // column-wise formats of a symmetric matrix are the transposed row-wise ones
+    fn entries(&self, n: usize) -> Vec<(usize, usize)> {
+        let mut entries = Vec::new();
+        for i in 0..n {
+            let columns = match self {
+                EdgeWeightFormat::FullMatrix => 0..n,
+                EdgeWeightFormat::UpperRow | EdgeWeightFormat::LowerCol => i + 1..n,
+                EdgeWeightFormat::LowerRow | EdgeWeightFormat::UpperCol => 0..i,
+                EdgeWeightFormat::UpperDiagRow | EdgeWeightFormat::LowerDiagCol => i..n,
+                EdgeWeightFormat::LowerDiagRow | EdgeWeightFormat::UpperDiagCol => 0..i + 1,
+            };
+
+            for j in columns {
+                entries.push((i, j));
+            }
+        }
+
+        entries
+    }
+}
+
+enum Section {
+    Header,
+    NodeCoords,
+    EdgeWeights,
+    DisplayData,
+    Ignored,
+}
+
+struct Line<'a> {
+    number: usize,
+    tokens: Vec<&'a str>,
+}
+
+fn parse_number(line: usize, value: &str) -> Result<f64, TsplibError> {
+    value.parse::<f64>().map_err(|_| TsplibError::InvalidNumber { line, value: value.to_string() })
+}
+
+fn parse_coords(lines: &[Line], n: usize) -> Result<Vec<Point>, TsplibError> {
+    let mut coords: Vec<Option<Point>> = (0..n).map(|_| None).collect();
+
+    for line in lines {
+        if line.tokens.len() < 3 {
+            return Err(TsplibError::InvalidLine { line: line.number, content: line.tokens.join(" ") });
+        }
+
+        let id = parse_number(line.number, line.tokens[0])? as i64;
+        if id < 1 || id > n as i64 {
+            return Err(TsplibError::InvalidNodeId { line: line.number, id });
+        }
+
+        let slot = &mut coords[id as usize - 1];
+        if slot.is_some() {
+            return Err(TsplibError::DuplicateNode { line: line.number, id });
+        }
+
+        let x = parse_number(line.number, line.tokens[1])?;
+        let y = parse_number(line.number, line.tokens[2])?;
+        *slot = Some(Point::new(x, y));
+    }
+
+    let found = coords.iter().filter(|c| c.is_some()).count();
+    if found != n {
+        return Err(TsplibError::WrongNumberOfEntries { section: "NODE_COORD_SECTION", expected: n, found });
+    }
+
+    Ok(coords.into_iter().map(|c| c.unwrap()).collect())
+}
+
+pub fn parse(input: &str) -> Result<Graph, TsplibError> {
+    let mut dimension: Option<usize> = None;
+    let mut weight_type: Option<EdgeWeightType> = None;
+    let mut weight_format: Option<EdgeWeightFormat> = None;
+    let mut directed = false;
+
+    let mut section = Section::Header;
+    let mut coord_lines: Vec<Line> = Vec::new();
+    let mut display_lines: Vec<Line> = Vec::new();
+    let mut weight_tokens: Vec<(usize, &str)> = Vec::new();
+
+    for (i, raw) in input.lines().enumerate() {
+        let number = i + 1;
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        // a line starting with a letter is either a header entry or a section keyword
+        if trimmed.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            let (key, value) = match trimmed.split_once(':') {
+                Some((key, value)) => (key.trim(), Some(value.trim())),
+                None => (trimmed, None),
+            };
+
+            section = match (key, value) {
+                ("EOF", _) => break,
+                ("NODE_COORD_SECTION", _) => Section::NodeCoords,
+                ("EDGE_WEIGHT_SECTION", _) => Section::EdgeWeights,
+                ("DISPLAY_DATA_SECTION", _) => Section::DisplayData,
+                (_, None) if key.ends_with("_SECTION") => Section::Ignored,
+                (_, None) => return Err(TsplibError::InvalidLine { line: number, content: trimmed.to_string() }),
+                (_, Some(value)) => {
+                    match key {
+                        "TYPE" => match value {
+                            "TSP" => directed = false,
+                            "ATSP" => directed = true,
+                            _ => return Err(TsplibError::UnsupportedProblemType(value.to_string())),
+                        },
+                        "DIMENSION" => {
+                            let n = parse_number(number, value)?;
+                            if n.fract() != 0.0 || n < 1.0 || n > MAX_DIMENSION as f64 {
+                                return Err(TsplibError::InvalidDimension { line: number, value: value.to_string() });
+                            }
+                            dimension = Some(n as usize);
+                        },
+                        "EDGE_WEIGHT_TYPE" => weight_type = Some(EdgeWeightType::parse(value)?),
+                        // FUNCTION is the format implied by coordinate based weight types
+                        "EDGE_WEIGHT_FORMAT" if value != "FUNCTION" => {
+                            weight_format = Some(EdgeWeightFormat::parse(value)?);
+                        },
+                        // NAME, COMMENT, NODE_COORD_TYPE, DISPLAY_DATA_TYPE, CAPACITY...
+                        _ => {}
+                    }
+                    Section::Header
+                }
+            };
+            continue;
+        }
+
+        let tokens = trimmed.split_whitespace().collect::<Vec<&str>>();
+        match section {
+            Section::NodeCoords => coord_lines.push(Line { number, tokens }),
+            Section::DisplayData => display_lines.push(Line { number, tokens }),
+            Section::EdgeWeights => weight_tokens.extend(tokens.into_iter().map(|t| (number, t))),
+            Section::Ignored => {},
+            Section::Header => return Err(TsplibError::InvalidLine { line: number, content: trimmed.to_string() }),
+        }
+    }
+
+    let n = dimension.ok_or(TsplibError::MissingDimension)?;
+    let weight_type = weight_type.ok_or(TsplibError::MissingEdgeWeightType)?;
+
+    // asymmetric instances keep both directions of every pair
+    let mut graph = Graph::new();
+    graph.set_directed(directed);
+
+    match weight_type.metric() {
+        None => {
+            let format = weight_format.ok_or(TsplibError::MissingEdgeWeightFormat)?;
+            if weight_tokens.is_empty() {
+                return Err(TsplibError::MissingSection("EDGE_WEIGHT_SECTION"));
+            }
+
+            // checked before the entries are listed, which takes O(n^2) memory
+            let expected = format.number_of_entries(n);
+            if expected != weight_tokens.len() {
+                return Err(TsplibError::WrongNumberOfEntries {
+                    section: "EDGE_WEIGHT_SECTION",
+                    expected,
+                    found: weight_tokens.len(),
+                });
+            }
+            let entries = format.entries(n);
+
+            let mut matrix = vec![vec![0; n]; n];
+            for (&(i, j), &(line, token)) in entries.iter().zip(weight_tokens.iter()) {
+                let weight = parse_number(line, token)?.round() as i32;
+                matrix[i][j] = weight;
+                if format != EdgeWeightFormat::FullMatrix {
+                    matrix[j][i] = weight;
+                }
+            }
+
+            // explicit instances may still provide coordinates for drawing
+            match display_lines.is_empty() && coord_lines.is_empty() {
+                true => graph.fill_with_circular_layout(n as i32),
+                false => {
+                    let lines = if coord_lines.is_empty() { &display_lines } else { &coord_lines };
+                    graph.add_nodes(parse_coords(lines, n)?);
+                }
+            }
+
+            for (u, row) in matrix.iter().enumerate() {
+                for (v, &weight) in row.iter().enumerate() {
+                    if u < v || (directed && u != v) {
+                        graph.add_edge(u as i32, v as i32, weight);
+                    }
+                }
+            }
+        },
+        Some(metric) => {
+            if coord_lines.is_empty() {
+                return Err(TsplibError::MissingSection("NODE_COORD_SECTION"));
+            }
+
+            graph.add_nodes(parse_coords(&coord_lines, n)?);
+            graph.set_metric(metric);
+            if n > 1 {
+                graph.fill_with_edges_full();
+            }
+        }
+    }
+
+    Ok(graph)
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Graph, TsplibError> {
+    let content = fs::read_to_string(path)?;
+    parse(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tour_length(graph: &Graph, tour: &[i32]) -> i32 {
+        let n = tour.len();
+        (0..n)
+            .map(|i| graph.get_edge_from_lookup(tour[i], tour[(i + 1) % n]).unwrap().weight)
+            .sum()
+    }
+
+    #[test]
+    fn test_euc_2d() {
+        let input = "\
+NAME : square
+TYPE : TSP
+DIMENSION : 4
+EDGE_WEIGHT_TYPE : EUC_2D
+NODE_COORD_SECTION
+1 0 0
+3 3 4
+2 0 4
+4 3 0.6
+EOF
+";
+        let graph = parse(input).unwrap();
+        assert_eq!(graph.get_number_of_nodes(), 4);
+        assert_eq!(graph.edges.len(), 6);
+        assert_eq!(graph.get_edge_from_lookup(0, 2).unwrap().weight, 5);
+        assert_eq!(graph.get_edge_from_lookup(0, 1).unwrap().weight, 4);
+        // sqrt(9 + 0.36) = 3.059 -> 3
+        assert_eq!(graph.get_edge_from_lookup(0, 3).unwrap().weight, 3);
+        // 3.4 -> 3
+        assert_eq!(graph.get_edge_from_lookup(2, 3).unwrap().weight, 3);
+    }
+
+    #[test]
+    fn test_ceil_2d_and_att() {
+        let coords = "NODE_COORD_SECTION\n1 0 0\n2 3 0.5\nEOF\n";
+
+        let ceil = parse(&format!("DIMENSION: 2\nEDGE_WEIGHT_TYPE: CEIL_2D\n{}", coords)).unwrap();
+        assert_eq!(ceil.get_edge_from_lookup(0, 1).unwrap().weight, 4);
+
+        // sqrt(9.25 / 10) = 0.96 -> nint 1
+        let att = parse(&format!("DIMENSION: 2\nEDGE_WEIGHT_TYPE: ATT\n{}", coords)).unwrap();
+        assert_eq!(att.get_edge_from_lookup(0, 1).unwrap().weight, 1);
+
+        let coords = "NODE_COORD_SECTION\n1 0 0\n2 30 40\nEOF\n";
+        // sqrt(2500 / 10) = 15.81 -> nint 16
+        let att = parse(&format!("DIMENSION: 2\nEDGE_WEIGHT_TYPE: ATT\n{}", coords)).unwrap();
+        assert_eq!(att.get_edge_from_lookup(0, 1).unwrap().weight, 16);
+    }
+
+    #[test]
+    fn test_geo_burma14() {
+        let input = "\
+NAME: burma14
+TYPE: TSP
+DIMENSION: 14
+EDGE_WEIGHT_TYPE: GEO
+EDGE_WEIGHT_FORMAT: FUNCTION
+DISPLAY_DATA_TYPE: COORD_DISPLAY
+NODE_COORD_SECTION
+   1  16.47       96.10
+   2  16.47       94.44
+   3  20.09       92.54
+   4  22.39       93.37
+   5  25.23       97.24
+   6  22.00       96.05
+   7  20.47       97.02
+   8  17.20       96.29
+   9  16.30       97.38
+  10  14.05       98.12
+  11  16.53       97.38
+  12  21.52       95.59
+  13  19.41       97.13
+  14  20.09       94.55
+EOF
+";
+        let graph = parse(input).unwrap();
+        let optimal = [1, 2, 14, 3, 4, 5, 6, 12, 7, 13, 8, 11, 9, 10].map(|i| i - 1);
+        assert_eq!(tour_length(&graph, &optimal), 3323);
+    }
+
+    #[test]
+    fn test_explicit_formats() {
+        let full = "\
+DIMENSION: 3
+EDGE_WEIGHT_TYPE: EXPLICIT
+EDGE_WEIGHT_FORMAT: FULL_MATRIX
+EDGE_WEIGHT_SECTION
+0 1 2
+1 0 3
+2 3 0
+EOF
+";
+        let upper_row = "\
+DIMENSION: 3
+EDGE_WEIGHT_TYPE: EXPLICIT
+EDGE_WEIGHT_FORMAT: UPPER_ROW
+EDGE_WEIGHT_SECTION
+1 2 3
+EOF
+";
+        let lower_diag_row = "\
+DIMENSION: 3
+EDGE_WEIGHT_TYPE: EXPLICIT
+EDGE_WEIGHT_FORMAT: LOWER_DIAG_ROW
+EDGE_WEIGHT_SECTION
+0 1
+0 2 3 0
+EOF
+";
+        let upper_col = "\
+DIMENSION: 3
+EDGE_WEIGHT_TYPE: EXPLICIT
+EDGE_WEIGHT_FORMAT: UPPER_COL
+EDGE_WEIGHT_SECTION
+1
+2 3
+EOF
+";
+        for input in [full, upper_row, lower_diag_row, upper_col] {
+            let graph = parse(input).unwrap();
+            assert_eq!(graph.get_number_of_nodes(), 3);
+            assert_eq!(graph.get_edge_from_lookup(0, 1).unwrap().weight, 1);
+            assert_eq!(graph.get_edge_from_lookup(0, 2).unwrap().weight, 2);
+            assert_eq!(graph.get_edge_from_lookup(1, 2).unwrap().weight, 3);
+        }
+    }
+
+    #[test]
+    fn test_atsp() {
+        let input = "\
+NAME: asym3
+TYPE: ATSP
+DIMENSION: 3
+EDGE_WEIGHT_TYPE: EXPLICIT
+EDGE_WEIGHT_FORMAT: FULL_MATRIX
+EDGE_WEIGHT_SECTION
+9999 1 2
+4 9999 3
+5 6 9999
+EOF
+";
+        let graph = parse(input).unwrap();
+        assert!(graph.is_directed());
+        assert_eq!(graph.edges.len(), 6);
+        assert_eq!(graph.get_edge_from_lookup(0, 1).unwrap().weight, 1);
+        assert_eq!(graph.get_edge_from_lookup(1, 0).unwrap().weight, 4);
+        assert_eq!(graph.get_edge_from_lookup(2, 1).unwrap().weight, 6);
+        assert_eq!(tour_length(&graph, &[0, 1, 2]), 1 + 3 + 5);
+        assert_eq!(tour_length(&graph, &[0, 2, 1]), 2 + 6 + 4);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(matches!(
+            parse("EDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n1 0 0\n"),
+            Err(TsplibError::MissingDimension)
+        ));
+        assert!(matches!(
+            parse("DIMENSION: 2\nEDGE_WEIGHT_TYPE: MAN_3D\n"),
+            Err(TsplibError::UnsupportedEdgeWeightType(_))
+        ));
+        assert!(matches!(
+            parse("TYPE: CVRP\nDIMENSION: 2\n"),
+            Err(TsplibError::UnsupportedProblemType(_))
+        ));
+        assert!(matches!(
+            parse("DIMENSION: 2\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n1 0 0\n3 1 1\n"),
+            Err(TsplibError::InvalidNodeId { line: 5, id: 3 })
+        ));
+        assert!(matches!(
+            parse("DIMENSION: 2\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n1 0 0\n1 1 1\n"),
+            Err(TsplibError::DuplicateNode { line: 5, id: 1 })
+        ));
+        assert!(matches!(
+            parse("DIMENSION: 2\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n1 0 x\n2 1 1\n"),
+            Err(TsplibError::InvalidNumber { line: 4, .. })
+        ));
+        assert!(matches!(
+            parse("DIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: UPPER_ROW\nEDGE_WEIGHT_SECTION\n1 2\n"),
+            Err(TsplibError::WrongNumberOfEntries { expected: 3, found: 2, .. })
+        ));
+        assert!(matches!(
+            parse("DIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_SECTION\n1 2 3\n"),
+            Err(TsplibError::MissingEdgeWeightFormat)
+        ));
+        assert!(matches!(
+            parse("DIMENSION: 2\nNODE_COORD_SECTION\n1 0 0\n2 1 1\n"),
+            Err(TsplibError::MissingEdgeWeightType)
+        ));
+        for dimension in ["0", "-3", "2.5", "1e12"] {
+            assert!(matches!(
+                parse(&format!("NAME: x\nDIMENSION: {}\nEDGE_WEIGHT_TYPE: EUC_2D\n", dimension)),
+                Err(TsplibError::InvalidDimension { line: 2, .. })
+            ), "{}", dimension);
+        }
+        // the entry count is checked before the n^2 entries are listed
+        assert!(matches!(
+            parse("DIMENSION: 100000\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\nEDGE_WEIGHT_SECTION\n1 2\n"),
+            Err(TsplibError::WrongNumberOfEntries { expected: 10_000_000_000, found: 2, .. })
+        ));
+    }
+}
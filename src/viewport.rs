@@ -0,0 +1,64 @@
+use nannou::prelude::*;
+
+use crate::graph::Point;
+
+// Maps graph coordinates into the window, the bounding box of the graph is scaled
+// uniformly so that it fits the window without stretching and is centred in it.
+// Larger graph y values are drawn higher up.
+pub(crate) struct Viewport {
+    scale: f64,
+    centre: (f64, f64),
+    window_centre: (f64, f64),
+}
+
+impl Viewport {
+    pub(crate) fn new(bounding_box: (f64, f64, f64, f64), window: &Rect, margin: f32) -> Viewport {
+        let (top, bottom, left, right) = bounding_box;
+        let area = window.pad(margin);
+        let (width, height) = (right - left, bottom - top);
+
+        // a single point or points on a line only limit the scale in one direction
+        let scale = match (width > 0.0, height > 0.0) {
+            (true, true) => (area.w() as f64 / width).min(area.h() as f64 / height),
+            (true, false) => area.w() as f64 / width,
+            (false, true) => area.h() as f64 / height,
+            (false, false) => 1.0,
+        };
+
+        Viewport {
+            scale,
+            centre: ((left + right) / 2.0, (top + bottom) / 2.0),
+            window_centre: (window.x() as f64, window.y() as f64),
+        }
+    }
+
+    pub(crate) fn map(&self, point: &Point) -> Point2 {
+        let x = self.window_centre.0 + (point.x - self.centre.0) * self.scale;
+        let y = self.window_centre.1 + (point.y - self.centre.1) * self.scale;
+        pt2(x as f32, y as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preserves_aspect_ratio() {
+        let window = Rect::from_w_h(800.0, 600.0);
+        // 4000 x 1000 units, the width limits the scale
+        let viewport = Viewport::new((-500.0, 500.0, 1000.0, 5000.0), &window, 0.0);
+
+        assert_eq!(viewport.map(&Point::new(3000.0, 0.0)), pt2(0.0, 0.0));
+        assert_eq!(viewport.map(&Point::new(1000.0, -500.0)), pt2(-400.0, -100.0));
+        assert_eq!(viewport.map(&Point::new(5000.0, 500.0)), pt2(400.0, 100.0));
+
+        // margins shrink the drawing area on every side
+        let viewport = Viewport::new((0.0, 100.0, 0.0, 100.0), &window, 50.0);
+        assert_eq!(viewport.map(&Point::new(0.0, 0.0)), pt2(-250.0, -250.0));
+        assert_eq!(viewport.map(&Point::new(100.0, 100.0)), pt2(250.0, 250.0));
+
+        let viewport = Viewport::new((7.0, 7.0, 3.0, 3.0), &window, 0.0);
+        assert_eq!(viewport.map(&Point::new(3.0, 7.0)), pt2(0.0, 0.0));
+    }
+}